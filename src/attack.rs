@@ -0,0 +1,121 @@
+use rltk::{DiceType, RandomNumberGenerator, RGB};
+
+/// how one attack roll came out
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AttackRoll {
+    // a natural 1, misses whatever the bonuses
+    Fumble,
+    Miss,
+    Hit,
+    // a natural 20, always hits and rolls the damage dice twice
+    Critical,
+}
+
+/// log colour for an attack that did damage, red when the player is the one bleeding
+pub fn hit_color(target_is_player: bool) -> RGB {
+    if target_is_player {
+        RGB::named(rltk::RED)
+    } else {
+        RGB::named(rltk::ORANGE)
+    }
+}
+
+/// an unarmoured target with no defense is hit on 10 or better
+pub fn armour_class(defense: i32) -> i32 {
+    10 + defense
+}
+
+/// shooting loses 1 to hit for every 3 tiles to the target
+pub fn range_penalty(distance: f32) -> i32 {
+    (distance / 3.0) as i32
+}
+
+/// a d20 that came up `natural`, plus `to_hit`, against the target's armour class
+pub fn attack_roll(natural: i32, to_hit: i32, armour_class: i32) -> AttackRoll {
+    match natural {
+        1 => AttackRoll::Fumble,
+        20 => AttackRoll::Critical,
+        _ if natural + to_hit >= armour_class => AttackRoll::Hit,
+        _ => AttackRoll::Miss,
+    }
+}
+
+/// a hit always does at least 1, a critical doubles the dice but not the bonus
+pub fn roll_damage(rng: &mut RandomNumberGenerator, damage: DiceType, critical: bool) -> i32 {
+    let n_dice = if critical { damage.n_dice * 2 } else { damage.n_dice };
+    (rng.roll_dice(n_dice, damage.die_type) + damage.bonus).max(1)
+}
+
+/// roll to hit and, when it lands, for damage, which is 0 on a miss
+pub fn swing(rng: &mut RandomNumberGenerator, to_hit: i32, armour_class: i32, damage: DiceType) -> (AttackRoll, i32) {
+    let roll = attack_roll(rng.roll_dice(1, 20), to_hit, armour_class);
+    let amount = match roll {
+        AttackRoll::Hit => roll_damage(rng, damage, false),
+        AttackRoll::Critical => roll_damage(rng, damage, true),
+        AttackRoll::Fumble | AttackRoll::Miss => 0,
+    };
+    (roll, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_twenty_always_hits() {
+        assert_eq!(attack_roll(20, -10, 100), AttackRoll::Critical);
+    }
+
+    #[test]
+    fn natural_one_always_misses() {
+        assert_eq!(attack_roll(1, 100, 0), AttackRoll::Fumble);
+    }
+
+    #[test]
+    fn meeting_the_armour_class_hits() {
+        assert_eq!(attack_roll(10, 2, armour_class(2)), AttackRoll::Hit);
+        assert_eq!(attack_roll(9, 2, armour_class(2)), AttackRoll::Miss);
+    }
+
+    #[test]
+    fn longer_shots_are_harder() {
+        assert_eq!(range_penalty(1.0), 0);
+        assert_eq!(range_penalty(3.0), 1);
+        assert_eq!(range_penalty(8.5), 2);
+    }
+
+    #[test]
+    fn criticals_double_the_dice_not_the_bonus() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let dice = DiceType::new(2, 1, 3);
+        assert_eq!(roll_damage(&mut rng, dice, false), 5);
+        assert_eq!(roll_damage(&mut rng, dice, true), 7);
+    }
+
+    #[test]
+    fn hits_never_do_less_than_one() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        assert_eq!(roll_damage(&mut rng, DiceType::new(1, 1, -5), false), 1);
+    }
+
+    #[test]
+    fn even_odds_still_hurt() {
+        // power 4 against defense 4 used to do nothing at all
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let dealt: i32 = (0..100)
+            .map(|_| swing(&mut rng, 4, armour_class(4), DiceType::new(1, 4, 0)).1)
+            .sum();
+        assert!(dealt > 0);
+    }
+
+    #[test]
+    fn same_seed_same_fight() {
+        let fight = |seed| {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            (0..50)
+                .map(|_| swing(&mut rng, 3, armour_class(2), DiceType::new(1, 6, 2)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fight(42), fight(42));
+    }
+}
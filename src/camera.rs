@@ -0,0 +1,95 @@
+use super::{Map, Player, Position, Renderable, TileType, ViewShed};
+use rltk::{Point, Rltk, RGB};
+use specs::prelude::*;
+
+/// the part of the 80x50 console the map is drawn in,
+/// the bottom rows are left for the message panel
+pub const VIEW_WIDTH: i32 = 80;
+pub const VIEW_HEIGHT: i32 = 43;
+
+/// the world rectangle on screen, centred on `center`: (min_x, min_y, max_x, max_y),
+/// max exclusive
+pub fn screen_bounds(center: Point) -> (i32, i32, i32, i32) {
+    let min_x = center.x - VIEW_WIDTH / 2;
+    let min_y = center.y - VIEW_HEIGHT / 2;
+    (min_x, min_y, min_x + VIEW_WIDTH, min_y + VIEW_HEIGHT)
+}
+
+/// world position of a screen cell, whatever the camera follows sits in the middle
+pub fn screen_to_world(ecs: &World, screen: Point) -> Point {
+    let (min_x, min_y, _, _) = screen_bounds(*ecs.fetch::<Point>());
+    Point::new(screen.x + min_x, screen.y + min_y)
+}
+
+/// screen cell of a world position, None when it is off screen
+pub fn world_to_screen(ecs: &World, world: Point) -> Option<Point> {
+    let (min_x, min_y, max_x, max_y) = screen_bounds(*ecs.fetch::<Point>());
+    if world.x < min_x || world.x >= max_x || world.y < min_y || world.y >= max_y {
+        return None;
+    }
+    Some(Point::new(world.x - min_x, world.y - min_y))
+}
+
+fn tile_glyph(tile: TileType) -> (rltk::FontCharType, RGB) {
+    match tile {
+        TileType::Wall => (rltk::to_cp437('#'), RGB::from_f32(0., 1.0, 0.)),
+        TileType::Floor => (rltk::to_cp437('.'), RGB::from_f32(0., 0.5, 0.5)),
+        TileType::DownStairs => (rltk::to_cp437('>'), RGB::from_f32(0., 1.0, 1.0)),
+    }
+}
+
+/// the map around the player and everything the player can see on it
+pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let (min_x, min_y, max_x, max_y) = screen_bounds(player_pos);
+
+    let players = ecs.read_storage::<Player>();
+    let viewsheds = ecs.read_storage::<ViewShed>();
+    let Some((_player, viewshed)) = (&players, &viewsheds).join().next() else {
+        return;
+    };
+
+    for (screen_y, y) in (min_y..max_y).enumerate() {
+        for (screen_x, x) in (min_x..max_x).enumerate() {
+            if x < 0 || x >= map.width || y < 0 || y >= map.height {
+                continue;
+            }
+            let idx = map.xy_idx(x, y);
+            if map.revealed_tiles[idx] {
+                let (glyph, mut fg) = tile_glyph(map.tiles[idx]);
+                if !viewshed.visible_tiles.contains(&Point::new(x, y)) {
+                    fg = fg.to_greyscale()
+                }
+                ctx.set(screen_x as i32, screen_y as i32, fg, RGB::from_f32(0., 0., 0.), glyph);
+            }
+        }
+    }
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+
+    // items first, creatures on top of them
+    let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
+    data.sort_by_key(|(_pos, render)| std::cmp::Reverse(render.render_order));
+    for (pos, render) in data {
+        let pt = Point::new(pos.x, pos.y);
+        if pos.x >= min_x && pos.x < max_x && pos.y >= min_y && pos.y < max_y && viewshed.visible_tiles.contains(&pt) {
+            ctx.set(pos.x - min_x, pos.y - min_y, render.fg, render.bg, render.glyph);
+        }
+    }
+}
+
+/// every tile of a generation snapshot around `center`, no fog and nothing greyed out
+pub fn render_snapshot(map: &Map, center: Point, ctx: &mut Rltk) {
+    let (min_x, min_y, max_x, max_y) = screen_bounds(center);
+    for (screen_y, y) in (min_y..max_y).enumerate() {
+        for (screen_x, x) in (min_x..max_x).enumerate() {
+            if x < 0 || x >= map.width || y < 0 || y >= map.height {
+                continue;
+            }
+            let (glyph, fg) = tile_glyph(map.tiles[map.xy_idx(x, y)]);
+            ctx.set(screen_x as i32, screen_y as i32, fg, RGB::from_f32(0., 0., 0.), glyph);
+        }
+    }
+}
@@ -1,42 +1,57 @@
 use rltk::RGB;
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
 use specs::prelude::*;
+use specs::saveload::{ConvertSaveload, Marker};
 use specs_derive::*;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Renderable {
     pub glyph: rltk::FontCharType,
     pub fg: RGB,
     pub bg: RGB,
+    pub render_order: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Player {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ViewShed {
     pub visible_tiles: Vec<rltk::Point>,
     pub range: i32,
     pub dirty: bool,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Monster {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Name {
     pub name: String,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
-#[derive(Component, Debug)]
+/// nothing can be seen through the tile this stands on
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct BlocksVisibility {}
+
+/// a closed door also carries BlocksTile and BlocksVisibility, opening it drops both
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Door {
+    pub open: bool,
+}
+
+/// power is added to every to-hit roll, defense raises the armour class
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
@@ -44,24 +59,298 @@ pub struct CombatStats {
     pub power: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, Debug)]
+/// one hit waiting to land, `from` gets the credit if it is the killing blow
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub amount: i32,
+    // None for damage nobody dealt, like poison
+    pub from: Option<Entity>,
+}
+
+/// never saved, the damage system empties it every turn
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage {
-    pub amounts: Vec<i32>,
+    pub hits: Vec<Hit>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32, from: Option<Entity>) {
         // insert component in runtime, dynamically.
+        let hit = Hit { amount, from };
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amounts.push(amount);
+            suffering.hits.push(hit);
         } else {
-            let dmg = SufferDamage { amounts: vec![amount] };
+            let dmg = SufferDamage { hits: vec![hit] };
             store.insert(victim, dmg).expect("unable to insert damage");
         }
     }
 }
+
+/// xp collected so far, counted from the start of the game rather than the level
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+/// xp for whoever lands the killing blow
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct GrantsExperience {
+    pub xp: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Perk {
+    // shots lose nothing to range
+    Marksman,
+    // poison can't take hold
+    Hardy,
+}
+
+impl Perk {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Perk::Marksman => "Marksman: no penalty for long shots",
+            Perk::Hardy => "Hardy: immune to poison",
+        }
+    }
+}
+
+/// perks picked on level up, each can only be taken once
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Perks {
+    pub taken: Vec<Perk>,
+}
+
+impl Perks {
+    pub fn has(&self, perk: Perk) -> bool {
+        self.taken.contains(&perk)
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Item {}
+
+/// carried items have no Position, only the owner they travel with
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct InPack {
+    pub owner: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToPickupItem {
+    pub collected_by: Entity,
+    pub item: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToDropItem {
+    pub item: Entity,
+}
+
+/// destroyed once used
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Consumable {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesHealing {
+    pub heal_amount: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsDamage {
+    pub damage: i32,
+}
+
+/// the item goes off around its target, hitting everything the centre can see
+/// within `radius`, the user included
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AreaOfEffect {
+    pub radius: i32,
+}
+
+/// the item needs a target tile within this many tiles of the user
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ranged {
+    pub range: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToUseItem {
+    pub item: Entity,
+    pub target: Option<rltk::Point>,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusKind {
+    // loses potency hp every turn
+    Poison,
+    // stumbles about instead of acting
+    Confusion,
+    // skips every turn
+    Sleep,
+    // gains potency hp every turn
+    Regeneration,
+}
+
+impl StatusKind {
+    /// how the HUD and tooltips describe someone under the effect
+    pub fn describe(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "Poisoned",
+            StatusKind::Confusion => "Confused",
+            StatusKind::Sleep => "Asleep",
+            StatusKind::Regeneration => "Regenerating",
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: i32,
+    // damage or healing per turn, unused by the rest
+    #[serde(default)]
+    pub potency: i32,
+}
+
+/// everything currently affecting a creature, StatusEffectSystem counts them down
+/// once per turn and drops the component when the last one wears off
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct StatusEffects {
+    pub active: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// the same effect twice doesn't stack, the longer and stronger one is kept
+    pub fn add(store: &mut WriteStorage<StatusEffects>, victim: Entity, effect: StatusEffect) {
+        if let Some(statuses) = store.get_mut(victim) {
+            match statuses.active.iter_mut().find(|active| active.kind == effect.kind) {
+                Some(active) => {
+                    active.turns = active.turns.max(effect.turns);
+                    active.potency = active.potency.max(effect.potency);
+                }
+                None => statuses.active.push(effect),
+            }
+        } else {
+            store
+                .insert(victim, StatusEffects { active: vec![effect] })
+                .expect("unable to insert status effects");
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|active| active.kind == kind)
+    }
+}
+
+/// using the item puts the effect on the target, or on the user when it isn't aimed
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub effect: StatusEffect,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    Melee,
+    Ranged,
+    Shield,
+    Armor,
+    Helmet,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+/// worn items leave the pack, they come back to it when removed
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+/// the damage dice of a wielded weapon, rolled instead of the wielder's natural attack
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MeleeWeapon {
+    pub damage: rltk::DiceType,
+}
+
+/// a bow or sling, shot at a tile up to `range` away instead of swung
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub damage: rltk::DiceType,
+}
+
+/// shots for ranged weapons, every shot uses one up, an empty stack is destroyed
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ammunition {
+    pub rounds: i32,
+}
+
+/// fire the wielded ranged weapon at a tile, the shot stops at whatever is in the way
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToShoot {
+    pub target: rltk::Point,
+}
+
+/// fists, claws and teeth, used when nothing is wielded
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct NaturalAttack {
+    pub damage: rltk::DiceType,
+}
+
+/// added to the wearer's to-hit rolls
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MeleePowerBonus {
+    pub power: i32,
+}
+
+/// added to the wearer's armour class
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct DefenseBonus {
+    pub defense: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToOpenDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToCloseDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
+
+/// a short lived glyph, gone after `lifetime_ms` of real time whatever the turn,
+/// particles are never saved
+#[derive(Component, Debug, Clone)]
+pub struct ParticleLifetime {
+    pub lifetime_ms: f32,
+}
+
+/// marker for every entity that should end up in the save file
+pub struct SerializeMe;
+
+/// carries the resources that are not components through the save file,
+/// it lives on a temporary entity only while saving and loading
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SerializationHelper {
+    pub map: super::Map,
+    pub run_state: super::RunState,
+    pub seed: u64,
+    pub rng: rltk::RandomNumberGenerator,
+    pub stats: super::GameStats,
+}
@@ -1,23 +1,49 @@
-use super::{CombatStats, Player, SufferDamage};
-use rltk::console;
+use super::{CombatStats, Experience, GameLog, GrantsExperience, LogEntry, Player, RunState, SufferDamage};
+use rltk::RGB;
 use specs::prelude::*;
 
+/// lands the hits of this turn, whoever takes the victim below 1 hp gets its xp
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
-        // WriteStorage<'a, Player>,
+        ReadStorage<'a, GrantsExperience>,
+        WriteStorage<'a, Experience>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut all_stats, mut damage) = data;
+        let (entities, mut all_stats, mut damage, rewards, mut experience, player_entity, mut log) = data;
 
-        for (one_stats, damage) in (&mut all_stats, &damage).join() {
-            one_stats.hp -= damage.amounts.iter().sum::<i32>();
+        let mut kills = Vec::new();
+        for (victim, one_stats, damage) in (&entities, &mut all_stats, &damage).join() {
+            for hit in damage.hits.iter() {
+                let was_alive = one_stats.hp > 0;
+                one_stats.hp -= hit.amount;
+                if was_alive && one_stats.hp < 1 {
+                    if let Some(killer) = hit.from {
+                        kills.push((killer, victim));
+                    }
+                }
+            }
         }
         damage.clear();
+
+        for (killer, victim) in kills {
+            let (Some(reward), Some(exp)) = (rewards.get(victim), experience.get_mut(killer)) else {
+                continue;
+            };
+            exp.xp += reward.xp;
+            if killer == *player_entity {
+                log.push(
+                    LogEntry::new("You", "gain").target(format!("{} xp", reward.xp)).color(RGB::named(rltk::YELLOW)),
+                );
+            }
+        }
     }
 }
 
@@ -31,7 +57,8 @@ pub fn delete_the_dead(ecs: &mut World) {
         for (ent, one_combat_stats) in (&entities, &all_combat_stats).join() {
             if one_combat_stats.hp < 1 {
                 if is_player(ecs, ent) {
-                    console::log("You are killed");
+                    // the player is never deleted, the game over screen needs its stats
+                    *ecs.write_resource::<RunState>() = RunState::GameOver;
                     continue;
                 }
                 dead.push(ent);
@@ -46,5 +73,64 @@ pub fn delete_the_dead(ecs: &mut World) {
 
 fn is_player(ecs: &World, ent: Entity) -> bool {
     let players = ecs.read_storage::<Player>();
-    return players.get(ent).is_some();
+    players.get(ent).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the player and a goblin worth 20 xp with 5 hp left
+    fn world_with_goblin() -> (World, Entity, Entity) {
+        let mut ecs = World::new();
+        ecs.register::<CombatStats>();
+        ecs.register::<SufferDamage>();
+        ecs.register::<GrantsExperience>();
+        ecs.register::<Experience>();
+        ecs.insert(GameLog::default());
+
+        let player = ecs
+            .create_entity()
+            .with(CombatStats { max_hp: 30, hp: 30, defense: 0, power: 0 })
+            .with(Experience { level: 1, xp: 0 })
+            .build();
+        let goblin = ecs
+            .create_entity()
+            .with(CombatStats { max_hp: 16, hp: 5, defense: 0, power: 0 })
+            .with(GrantsExperience { xp: 20 })
+            .build();
+        ecs.insert(player);
+        (ecs, player, goblin)
+    }
+
+    fn xp(ecs: &World, ent: Entity) -> i32 {
+        ecs.read_storage::<Experience>().get(ent).unwrap().xp
+    }
+
+    #[test]
+    fn the_killing_blow_earns_the_xp() {
+        let (ecs, player, goblin) = world_with_goblin();
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 3, None);
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 3, Some(player));
+        DamageSystem {}.run_now(&ecs);
+        assert_eq!(xp(&ecs, player), 20);
+    }
+
+    #[test]
+    fn softening_a_target_up_earns_nothing() {
+        let (ecs, player, goblin) = world_with_goblin();
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 3, Some(player));
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 3, None);
+        DamageSystem {}.run_now(&ecs);
+        assert_eq!(xp(&ecs, player), 0);
+    }
+
+    #[test]
+    fn the_dead_only_die_once() {
+        let (ecs, player, goblin) = world_with_goblin();
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 10, Some(player));
+        SufferDamage::new_damage(&mut ecs.write_storage(), goblin, 10, Some(player));
+        DamageSystem {}.run_now(&ecs);
+        assert_eq!(xp(&ecs, player), 20);
+    }
 }
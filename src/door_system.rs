@@ -0,0 +1,56 @@
+use super::{BlocksTile, BlocksVisibility, Door, Map, Position, Renderable, WantsToCloseDoor, WantsToOpenDoor};
+use specs::prelude::*;
+
+const OPEN_GLYPH: char = '/';
+const CLOSED_GLYPH: char = '+';
+
+/// opens and closes doors for whoever asked, the map picks up the change
+/// at the next MapIndexingSystem run
+pub struct DoorSystem {}
+
+impl<'a> System<'a> for DoorSystem {
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, WantsToOpenDoor>,
+        WriteStorage<'a, WantsToCloseDoor>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, BlocksTile>,
+        WriteStorage<'a, BlocksVisibility>,
+        WriteStorage<'a, Renderable>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, mut wants_open, mut wants_close, mut doors, mut blockers, mut view_blockers, mut renderables, positions) =
+            data;
+
+        for want in wants_open.drain().join() {
+            if let Some(door) = doors.get_mut(want.door) {
+                door.open = true;
+                blockers.remove(want.door);
+                view_blockers.remove(want.door);
+                if let Some(render) = renderables.get_mut(want.door) {
+                    render.glyph = rltk::to_cp437(OPEN_GLYPH);
+                }
+            }
+        }
+
+        for want in wants_close.drain().join() {
+            // something standing in the doorway keeps it open
+            let Some(pos) = positions.get(want.door) else { continue };
+            let idx = map.xy_idx(pos.x, pos.y);
+            if map.tile_content[idx].iter().any(|ent| *ent != want.door) {
+                continue;
+            }
+
+            if let Some(door) = doors.get_mut(want.door) {
+                door.open = false;
+                blockers.insert(want.door, BlocksTile {}).expect("unable to insert blocker");
+                view_blockers.insert(want.door, BlocksVisibility {}).expect("unable to insert view blocker");
+                if let Some(render) = renderables.get_mut(want.door) {
+                    render.glyph = rltk::to_cp437(CLOSED_GLYPH);
+                }
+            }
+        }
+    }
+}
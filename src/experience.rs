@@ -0,0 +1,153 @@
+use super::{CombatStats, Experience, GameLog, LogEntry, Perk, Perks};
+use rltk::RGB;
+use specs::prelude::*;
+
+/// total xp needed to reach `level`, every level costs 100 more than the last:
+/// 100 for level 2, 300 for level 3, 600 for level 4
+pub fn xp_to_reach(level: i32) -> i32 {
+    50 * level * (level - 1)
+}
+
+/// picked on the level up screen, on top of what every level brings
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LevelUpChoice {
+    Vitality,
+    Might,
+    Guard,
+    Perk(Perk),
+}
+
+impl LevelUpChoice {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            LevelUpChoice::Vitality => "Vitality: +10 max hp",
+            LevelUpChoice::Might => "Might: +1 power",
+            LevelUpChoice::Guard => "Guard: +1 defense",
+            LevelUpChoice::Perk(perk) => perk.describe(),
+        }
+    }
+}
+
+/// the attributes are always on offer, perks only until taken
+pub fn level_up_choices(perks: &Perks) -> Vec<LevelUpChoice> {
+    let mut choices = vec![LevelUpChoice::Vitality, LevelUpChoice::Might, LevelUpChoice::Guard];
+    for perk in [Perk::Marksman, Perk::Hardy] {
+        if !perks.has(perk) {
+            choices.push(LevelUpChoice::Perk(perk));
+        }
+    }
+    choices
+}
+
+/// whether the player has the xp for another level
+pub fn can_level_up(ecs: &World) -> bool {
+    let player = ecs.fetch::<Entity>();
+    ecs.read_storage::<Experience>()
+        .get(*player)
+        .is_some_and(|exp| exp.xp >= xp_to_reach(exp.level + 1))
+}
+
+/// what every level brings whatever the choice
+const HP_PER_LEVEL: i32 = 5;
+const POWER_PER_LEVEL: i32 = 1;
+// +1 defense on every level divisible by this
+const LEVELS_PER_DEFENSE: i32 = 2;
+
+/// the gains reaching `level` brings on top of the choice, for the level up screen
+pub fn describe_gains(level: i32) -> String {
+    let mut gains = vec![format!("+{} max hp", HP_PER_LEVEL), format!("+{} power", POWER_PER_LEVEL)];
+    if level % LEVELS_PER_DEFENSE == 0 {
+        gains.push("+1 defense".to_string());
+    }
+    let last = gains.pop().unwrap_or_default();
+    format!("{} and {} come anyway", gains.join(", "), last)
+}
+
+/// one level up: the gains from `describe_gains` plus the choice,
+/// the hp gained is healed straight away
+fn gain_level(exp: &mut Experience, stats: &mut CombatStats, perks: &mut Perks, choice: LevelUpChoice) {
+    exp.level += 1;
+
+    let mut hp_gain = HP_PER_LEVEL;
+    stats.power += POWER_PER_LEVEL;
+    if exp.level % LEVELS_PER_DEFENSE == 0 {
+        stats.defense += 1;
+    }
+
+    match choice {
+        LevelUpChoice::Vitality => hp_gain += 10,
+        LevelUpChoice::Might => stats.power += 1,
+        LevelUpChoice::Guard => stats.defense += 1,
+        LevelUpChoice::Perk(perk) => perks.taken.push(perk),
+    }
+    stats.max_hp += hp_gain;
+    stats.hp += hp_gain;
+}
+
+pub fn level_up(ecs: &mut World, choice: LevelUpChoice) {
+    let player = *ecs.fetch::<Entity>();
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut perks = ecs.write_storage::<Perks>();
+
+    let (Some(exp), Some(stats), Some(taken)) =
+        (experience.get_mut(player), combat_stats.get_mut(player), perks.get_mut(player))
+    else {
+        return;
+    };
+    gain_level(exp, stats, taken, choice);
+    ecs.write_resource::<GameLog>().push(
+        LogEntry::new("You", format!("reach level {}", exp.level)).color(RGB::named(rltk::YELLOW)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fresh() -> (Experience, CombatStats, Perks) {
+        (
+            Experience { level: 1, xp: 0 },
+            CombatStats { max_hp: 30, hp: 12, defense: 2, power: 5 },
+            Perks::default(),
+        )
+    }
+
+    #[test]
+    fn each_level_costs_more_than_the_last() {
+        assert_eq!(xp_to_reach(1), 0);
+        assert_eq!(xp_to_reach(2), 100);
+        assert_eq!(xp_to_reach(3), 300);
+        assert!((2..20).all(|level| xp_to_reach(level + 1) - xp_to_reach(level) > xp_to_reach(level) - xp_to_reach(level - 1)));
+    }
+
+    #[test]
+    fn every_level_raises_the_stats() {
+        let (mut exp, mut stats, mut perks) = fresh();
+        gain_level(&mut exp, &mut stats, &mut perks, LevelUpChoice::Might);
+        assert_eq!(exp.level, 2);
+        assert_eq!((stats.max_hp, stats.hp), (35, 17));
+        assert_eq!(stats.power, 7);
+        assert_eq!(stats.defense, 3);
+
+        gain_level(&mut exp, &mut stats, &mut perks, LevelUpChoice::Vitality);
+        assert_eq!((stats.max_hp, stats.hp), (50, 32));
+        assert_eq!(stats.power, 8);
+        assert_eq!(stats.defense, 3);
+    }
+
+    #[test]
+    fn the_screen_tells_what_every_level_brings() {
+        assert_eq!(describe_gains(2), "+5 max hp, +1 power and +1 defense come anyway");
+        assert_eq!(describe_gains(3), "+5 max hp and +1 power come anyway");
+    }
+
+    #[test]
+    fn perks_are_only_offered_once() {
+        let (mut exp, mut stats, mut perks) = fresh();
+        assert_eq!(level_up_choices(&perks).len(), 5);
+        gain_level(&mut exp, &mut stats, &mut perks, LevelUpChoice::Perk(Perk::Hardy));
+        assert!(perks.has(Perk::Hardy));
+        assert!(!level_up_choices(&perks).contains(&LevelUpChoice::Perk(Perk::Hardy)));
+    }
+}
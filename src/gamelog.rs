@@ -0,0 +1,75 @@
+use rltk::RGB;
+
+/// one line of the message log, kept structured so systems don't have to
+/// agree on sentence formatting, `text` puts it together for drawing
+pub struct LogEntry {
+    pub actor: String,
+    pub verb: String,
+    pub target: Option<String>,
+    pub amount: Option<i32>,
+    pub color: RGB,
+}
+
+impl LogEntry {
+    pub fn new<A: ToString, V: ToString>(actor: A, verb: V) -> LogEntry {
+        LogEntry {
+            actor: actor.to_string(),
+            verb: verb.to_string(),
+            target: None,
+            amount: None,
+            color: RGB::named(rltk::WHITE),
+        }
+    }
+
+    pub fn target<T: ToString>(mut self, target: T) -> LogEntry {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    pub fn amount(mut self, amount: i32) -> LogEntry {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn color(mut self, color: RGB) -> LogEntry {
+        self.color = color;
+        self
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = format!("{} {}", self.actor, self.verb);
+        if let Some(target) = &self.target {
+            text = format!("{} {}", text, target);
+        }
+        if let Some(amount) = self.amount {
+            text = format!("{} for {} hp", text, amount);
+        }
+        text + "."
+    }
+}
+
+/// every message of the current game, newest last
+#[derive(Default)]
+pub struct GameLog {
+    pub entries: Vec<LogEntry>,
+    // how many lines the panel is scrolled back from the newest entry
+    pub scroll: usize,
+}
+
+impl GameLog {
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+        // a new message always brings the panel back to the bottom
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        if self.scroll + 1 < self.entries.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
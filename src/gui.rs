@@ -0,0 +1,549 @@
+use super::experience::{self, LevelUpChoice};
+use super::{
+    blast_area, blast_centre, camera, line_of_fire, saveload_system, Ammunition, AreaOfEffect, CombatStats, Equipped, Experience, GameLog, GameStats, InPack, Map, Name,
+    Perks, Position, RunState, Seed, State, StatusEffects, StatusKind, TargetingFor, ViewShed,
+};
+use rltk::Point;
+use rltk::{Rltk, VirtualKeyCode, RGB};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MainMenuSelection {
+    NewGame,
+    LoadGame,
+    Quit,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MainMenuResult {
+    NoSelection { selected: MainMenuSelection },
+    Selected { selected: MainMenuSelection },
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum GameOverResult {
+    NoSelection,
+    QuitToMenu,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemMenuResult {
+    Cancel,
+    NoResponse,
+    Selected,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpResult {
+    NoResponse,
+    Selected { choice: LevelUpChoice },
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogViewResult {
+    Scrolled { offset: usize },
+    Close,
+}
+
+/// the status panel under the map: player stats on the first row,
+/// the message log below, PageUp/PageDown scroll the log,
+/// and a tooltip for whatever the mouse is over
+pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
+    let panel_y = camera::VIEW_HEIGHT;
+    let panel_h = 49 - panel_y;
+    ctx.draw_box(0, panel_y, camera::VIEW_WIDTH - 1, panel_h, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+
+    let seed = ecs.fetch::<Seed>();
+    ctx.print_color(2, panel_y, RGB::named(rltk::GREY), RGB::named(rltk::BLACK), format!(" seed: {} ", seed.0));
+
+    draw_statuses(ecs, ctx, panel_y + panel_h);
+    draw_experience(ecs, ctx, panel_y);
+    draw_stats(ecs, ctx, panel_y + 1);
+    draw_log(ecs, ctx, panel_y + 2, panel_y + panel_h - 1);
+    draw_tooltips(ecs, ctx);
+}
+
+fn status_color(kind: StatusKind) -> RGB {
+    match kind {
+        StatusKind::Poison => RGB::named(rltk::GREEN),
+        StatusKind::Confusion => RGB::named(rltk::MAGENTA),
+        StatusKind::Sleep => RGB::named(rltk::CYAN),
+        StatusKind::Regeneration => RGB::named(rltk::YELLOW),
+    }
+}
+
+/// the player's effects and their turns left, along the bottom edge of the panel;
+/// the top edge is taken by the seed and the xp. they stop short of the log's
+/// "-N more" marker in the bottom right corner
+fn draw_statuses(ecs: &World, ctx: &mut Rltk, y: i32) {
+    let player = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    let Some(effects) = statuses.get(*player) else {
+        return;
+    };
+
+    let right = camera::VIEW_WIDTH - 13;
+    let mut x = 2;
+    for effect in effects.active.iter() {
+        let text = format!(" {} {} ", effect.kind.describe(), effect.turns);
+        if x + text.len() as i32 > right {
+            break;
+        }
+        ctx.print_color(x, y, status_color(effect.kind), RGB::named(rltk::BLACK), &text);
+        x += text.len() as i32;
+    }
+}
+
+/// level and xp towards the next one, at the right end of the panel's top edge
+fn draw_experience(ecs: &World, ctx: &mut Rltk, y: i32) {
+    let player = ecs.fetch::<Entity>();
+    let experience = ecs.read_storage::<Experience>();
+    let Some(exp) = experience.get(*player) else {
+        return;
+    };
+
+    let text = format!(" Level {}  XP {}/{} ", exp.level, exp.xp, experience::xp_to_reach(exp.level + 1));
+    ctx.print_color(camera::VIEW_WIDTH - 2 - text.len() as i32, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &text);
+}
+
+/// name, health and effects of everything visible under the mouse,
+/// on the side of the cursor with more room
+fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
+    let (mouse_x, mouse_y) = ctx.mouse_pos();
+    if !(0..camera::VIEW_WIDTH).contains(&mouse_x) || !(0..camera::VIEW_HEIGHT).contains(&mouse_y) {
+        return;
+    }
+    let world = camera::screen_to_world(ecs, Point::new(mouse_x, mouse_y));
+
+    let player = ecs.fetch::<Entity>();
+    let viewsheds = ecs.read_storage::<ViewShed>();
+    if !viewsheds.get(*player).is_some_and(|viewshed| viewshed.visible_tiles.contains(&world)) {
+        return;
+    }
+
+    let names = ecs.read_storage::<Name>();
+    let positions = ecs.read_storage::<Position>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+
+    let mut lines: Vec<(String, RGB)> = Vec::new();
+    for (ent, name, pos) in (&ecs.entities(), &names, &positions).join() {
+        if pos.x != world.x || pos.y != world.y {
+            continue;
+        }
+        lines.push((name.name.clone(), RGB::named(rltk::WHITE)));
+        if let Some(stats) = combat_stats.get(ent) {
+            lines.push((format!(" HP: {}/{}", stats.hp, stats.max_hp), RGB::named(rltk::WHITE)));
+        }
+        if let Some(effects) = statuses.get(ent) {
+            for effect in effects.active.iter() {
+                lines.push((format!(" {} {}", effect.kind.describe(), effect.turns), status_color(effect.kind)));
+            }
+        }
+    }
+    if lines.is_empty() {
+        return;
+    }
+
+    let width = lines.iter().map(|(text, _)| text.len() as i32).max().unwrap_or(0) + 2;
+    let left = if mouse_x > camera::VIEW_WIDTH / 2 { mouse_x - width } else { mouse_x + 1 };
+    let top = mouse_y.min(camera::VIEW_HEIGHT - lines.len() as i32);
+    ctx.set_bg(mouse_x, mouse_y, RGB::named(rltk::MAGENTA));
+    for (i, (text, color)) in lines.iter().enumerate() {
+        let padded = format!(" {:width$}", text, width = (width - 1) as usize);
+        ctx.print_color(left, top + i as i32, *color, RGB::named(rltk::DIM_GREY), padded);
+    }
+}
+
+fn draw_stats(ecs: &World, ctx: &mut Rltk, y: i32) {
+    let player = ecs.fetch::<Entity>();
+    let all_stats = ecs.read_storage::<CombatStats>();
+    let map = ecs.fetch::<Map>();
+    let game_stats = ecs.fetch::<GameStats>();
+
+    if let Some(stats) = all_stats.get(*player) {
+        let health = format!("HP: {}/{}", stats.hp, stats.max_hp);
+        ctx.print_color(2, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &health);
+        ctx.draw_bar_horizontal(
+            15,
+            y,
+            20,
+            stats.hp.max(0),
+            stats.max_hp,
+            RGB::named(rltk::RED),
+            RGB::named(rltk::BLACK),
+        );
+        ctx.print_color(
+            37,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            format!("Pow: {}  Def: {}", stats.power, stats.defense),
+        );
+    }
+
+    ctx.print_color(
+        56,
+        y,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!("Depth: {}  Turn: {}", map.depth, game_stats.turns),
+    );
+}
+
+fn draw_log(ecs: &World, ctx: &mut Rltk, top: i32, bottom: i32) {
+    let log = ecs.fetch::<GameLog>();
+
+    let rows = (bottom - top + 1) as usize;
+    let visible = log.entries.iter().rev().skip(log.scroll).take(rows);
+    for (i, entry) in visible.enumerate() {
+        ctx.print_color(2, bottom - i as i32, entry.color, RGB::named(rltk::BLACK), entry.text());
+    }
+
+    if log.scroll > 0 {
+        ctx.print_color(camera::VIEW_WIDTH - 12, bottom + 1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), format!("-{} more", log.scroll));
+    }
+}
+
+/// full screen history, newest at the bottom, offset counts lines back from the end
+pub fn show_log(ecs: &World, ctx: &mut Rltk, offset: usize) -> LogViewResult {
+    let log = ecs.fetch::<GameLog>();
+    let rows = 46;
+    let max_offset = log.entries.len().saturating_sub(rows);
+    let offset = offset.min(max_offset);
+
+    ctx.draw_box(0, 0, camera::VIEW_WIDTH - 1, 49, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(3, 0, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Message Log");
+    ctx.print_color(3, 49, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Up/Down PageUp/PageDown to scroll, ESC to close");
+
+    let visible = log.entries.iter().rev().skip(offset).take(rows);
+    for (i, entry) in visible.enumerate() {
+        ctx.print_color(2, 47 - i as i32, entry.color, RGB::named(rltk::BLACK), entry.text());
+    }
+
+    match ctx.key {
+        None => LogViewResult::Scrolled { offset },
+        Some(key) => match key {
+            VirtualKeyCode::Escape | VirtualKeyCode::M => LogViewResult::Close,
+            VirtualKeyCode::Up | VirtualKeyCode::K => LogViewResult::Scrolled { offset: (offset + 1).min(max_offset) },
+            VirtualKeyCode::Down | VirtualKeyCode::J => LogViewResult::Scrolled { offset: offset.saturating_sub(1) },
+            VirtualKeyCode::PageUp => LogViewResult::Scrolled { offset: (offset + rows).min(max_offset) },
+            VirtualKeyCode::PageDown => LogViewResult::Scrolled { offset: offset.saturating_sub(rows) },
+            _ => LogViewResult::Scrolled { offset },
+        },
+    }
+}
+
+/// everything the player carries, one letter per item, a) first
+pub fn show_inventory(ecs: &World, ctx: &mut Rltk, title: &str) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InPack>();
+    let entities = ecs.entities();
+
+    let ammunition = ecs.read_storage::<Ammunition>();
+
+    let carried = (&entities, &backpack, &names)
+        .join()
+        .filter(|(_ent, pack, _name)| pack.owner == *player_entity)
+        .map(|(ent, _pack, name)| match ammunition.get(ent) {
+            Some(ammo) => (ent, format!("{} ({})", name.name, ammo.rounds)),
+            None => (ent, name.name.clone()),
+        })
+        .collect::<Vec<_>>();
+
+    item_menu(ctx, title, &carried)
+}
+
+/// everything the player is wearing or wielding
+pub fn remove_item_menu(ecs: &World, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
+
+    let worn = (&entities, &equipped, &names)
+        .join()
+        .filter(|(_ent, item, _name)| item.owner == *player_entity)
+        .map(|(ent, item, name)| (ent, format!("{} ({:?})", name.name, item.slot)))
+        .collect::<Vec<_>>();
+
+    item_menu(ctx, "Remove which item?", &worn)
+}
+
+fn item_menu(ctx: &mut Rltk, title: &str, items: &[(Entity, String)]) -> (ItemMenuResult, Option<Entity>) {
+    let count = items.len() as i32;
+    let y = 25 - (count / 2);
+    ctx.draw_box(15, y - 2, 31, count + 3, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(18, y - 2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), title);
+    ctx.print_color(18, y + count + 1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
+
+    for (j, (_ent, name)) in items.iter().enumerate() {
+        let y = y + j as i32;
+        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97 + j as rltk::FontCharType);
+        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+        ctx.print(21, y, name);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count {
+                (ItemMenuResult::Selected, Some(items[selection as usize].0))
+            } else {
+                (ItemMenuResult::NoResponse, None)
+            }
+        }
+    }
+}
+
+/// one reward per level, picked by letter, there is no backing out
+pub fn level_up_menu(ecs: &World, ctx: &mut Rltk) -> LevelUpResult {
+    let player = ecs.fetch::<Entity>();
+    let experience = ecs.read_storage::<Experience>();
+    let perks = ecs.read_storage::<Perks>();
+    let level = experience.get(*player).map_or(1, |exp| exp.level) + 1;
+    let choices = perks.get(*player).map(experience::level_up_choices).unwrap_or_default();
+
+    let count = choices.len() as i32;
+    let y = 25 - (count / 2);
+    ctx.draw_box(10, y - 2, 50, count + 3, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(13, y - 2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), format!("Level {}! Choose a reward", level));
+    ctx.print_color(13, y + count + 1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), experience::describe_gains(level));
+
+    for (j, choice) in choices.iter().enumerate() {
+        let y = y + j as i32;
+        ctx.set(12, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(13, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97 + j as rltk::FontCharType);
+        ctx.set(14, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+        ctx.print(16, y, choice.describe());
+    }
+
+    match ctx.key {
+        None => LevelUpResult::NoResponse,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count {
+                LevelUpResult::Selected { choice: choices[selection as usize] }
+            } else {
+                LevelUpResult::NoResponse
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum TargetingResult {
+    Cancel,
+    NoResponse { cursor: Point },
+    Selected { target: Point },
+}
+
+/// every visible tile within range of the player, a shot can't go at their own tile
+fn targetable_tiles(ecs: &World, range: i32, target_for: TargetingFor) -> Vec<Point> {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<ViewShed>();
+    let Some(viewshed) = viewsheds.get(*player_entity) else {
+        return Vec::new();
+    };
+    viewshed
+        .visible_tiles
+        .iter()
+        .filter(|tile| rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, **tile) <= range as f32)
+        .filter(|tile| target_for != TargetingFor::Shot || **tile != *player_pos)
+        .copied()
+        .collect()
+}
+
+/// creatures other than the player standing on one of the tiles, nearest first
+fn targets_in(ecs: &World, tiles: &[Point]) -> Vec<Point> {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let positions = ecs.read_storage::<Position>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+
+    let mut targets = (&ecs.entities(), &positions, &combat_stats)
+        .join()
+        .filter(|(ent, _pos, _stats)| *ent != *player_entity)
+        .map(|(_ent, pos, _stats)| Point::new(pos.x, pos.y))
+        .filter(|pt| tiles.contains(pt))
+        .collect::<Vec<_>>();
+    targets.sort_by(|a, b| {
+        let distance = |pt: &Point| rltk::DistanceAlg::Pythagoras.distance2d(player_pos, *pt);
+        distance(a).total_cmp(&distance(b))
+    });
+    targets
+}
+
+/// where the cursor starts: on the nearest creature in range, else on the
+/// nearest tile that can be picked, which is the player's own unless shooting
+pub fn initial_cursor(ecs: &World, range: i32, target_for: TargetingFor) -> Point {
+    let player_pos = *ecs.fetch::<Point>();
+    let tiles = targetable_tiles(ecs, range, target_for);
+    let distance = |pt: &&Point| rltk::DistanceAlg::Pythagoras.distance2d(player_pos, **pt);
+    let nearest = tiles.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))).copied();
+    targets_in(ecs, &tiles).first().copied().or(nearest).unwrap_or(player_pos)
+}
+
+/// move a cursor over the visible tiles within range, it can't leave them;
+/// Tab jumps to the next creature, Enter or F confirms, a left click picks
+/// a tile directly. the line of fire to the cursor is shown as well, and
+/// the area an item's blast would cover
+pub fn ranged_target(ecs: &World, ctx: &mut Rltk, range: i32, target_for: TargetingFor, cursor: Point) -> TargetingResult {
+    let player_pos = *ecs.fetch::<Point>();
+    let available_cells = targetable_tiles(ecs, range, target_for);
+    let radius = match target_for {
+        TargetingFor::Item(item) => ecs.read_storage::<AreaOfEffect>().get(item).map(|area| area.radius),
+        TargetingFor::Shot => None,
+    };
+    if available_cells.is_empty() {
+        return TargetingResult::Cancel;
+    }
+
+    ctx.print_color(5, 0, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Select Target:");
+
+    for tile in available_cells.iter() {
+        if let Some(screen) = camera::world_to_screen(ecs, *tile) {
+            ctx.set_bg(screen.x, screen.y, RGB::named(rltk::BLUE));
+        }
+    }
+    {
+        let map = ecs.fetch::<Map>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let is_creature = |ent| combat_stats.get(ent).is_some();
+        let path = line_of_fire(&map, player_pos, cursor, is_creature);
+        for tile in path.iter() {
+            if let Some(screen) = camera::world_to_screen(ecs, *tile) {
+                ctx.set_bg(screen.x, screen.y, RGB::named(rltk::DARK_CYAN));
+            }
+        }
+        if let Some(radius) = radius {
+            let centre = blast_centre(&map, player_pos, cursor, is_creature);
+            for tile in blast_area(&map, centre, radius) {
+                if let Some(screen) = camera::world_to_screen(ecs, tile) {
+                    ctx.set_bg(screen.x, screen.y, RGB::named(rltk::ORANGE));
+                }
+            }
+        }
+    }
+    if let Some(screen) = camera::world_to_screen(ecs, cursor) {
+        ctx.set_bg(screen.x, screen.y, RGB::named(rltk::CYAN));
+    }
+
+    // the mouse is on the screen, the cells are in the world
+    if ctx.left_click {
+        let mouse_pos = ctx.mouse_pos();
+        let mouse_point = camera::screen_to_world(ecs, Point::new(mouse_pos.0, mouse_pos.1));
+        if available_cells.contains(&mouse_point) {
+            return TargetingResult::Selected { target: mouse_point };
+        }
+        return TargetingResult::Cancel;
+    }
+
+    let step = |dx: i32, dy: i32| {
+        let moved = Point::new(cursor.x + dx, cursor.y + dy);
+        let cursor = if available_cells.contains(&moved) { moved } else { cursor };
+        TargetingResult::NoResponse { cursor }
+    };
+    match ctx.key {
+        None => TargetingResult::NoResponse { cursor },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => TargetingResult::Cancel,
+            VirtualKeyCode::Return | VirtualKeyCode::F if available_cells.contains(&cursor) => {
+                TargetingResult::Selected { target: cursor }
+            }
+            VirtualKeyCode::Tab => {
+                let targets = targets_in(ecs, &available_cells);
+                let next = targets
+                    .iter()
+                    .position(|pt| *pt == cursor)
+                    .map_or(0, |i| (i + 1) % targets.len());
+                TargetingResult::NoResponse { cursor: targets.get(next).copied().unwrap_or(cursor) }
+            }
+            VirtualKeyCode::Left | VirtualKeyCode::H | VirtualKeyCode::Numpad4 => step(-1, 0),
+            VirtualKeyCode::Right | VirtualKeyCode::L | VirtualKeyCode::Numpad6 => step(1, 0),
+            VirtualKeyCode::Up | VirtualKeyCode::K | VirtualKeyCode::Numpad8 => step(0, -1),
+            VirtualKeyCode::Down | VirtualKeyCode::J | VirtualKeyCode::Numpad2 => step(0, 1),
+            VirtualKeyCode::Y | VirtualKeyCode::Numpad7 => step(-1, -1),
+            VirtualKeyCode::U | VirtualKeyCode::Numpad9 => step(1, -1),
+            VirtualKeyCode::B | VirtualKeyCode::Numpad1 => step(-1, 1),
+            VirtualKeyCode::N | VirtualKeyCode::Numpad3 => step(1, 1),
+            _ => TargetingResult::NoResponse { cursor },
+        },
+    }
+}
+
+pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
+    let save_exists = saveload_system::does_save_exist();
+    let runstate = gs.ecs.fetch::<RunState>();
+
+    ctx.print_color_centered(15, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Hello Rogue");
+
+    let selection = match *runstate {
+        RunState::MainMenu { menu_selection } => menu_selection,
+        _ => MainMenuSelection::NewGame,
+    };
+
+    // continue is only offered when there is something to continue
+    let mut entries = vec![(MainMenuSelection::NewGame, "Begin New Game")];
+    if save_exists {
+        entries.push((MainMenuSelection::LoadGame, "Continue"));
+    }
+    entries.push((MainMenuSelection::Quit, "Quit"));
+
+    for (i, (entry, text)) in entries.iter().enumerate() {
+        let fg = if *entry == selection {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+        ctx.print_color_centered(24 + i as i32, fg, RGB::named(rltk::BLACK), text);
+    }
+
+    let current = entries.iter().position(|(entry, _)| *entry == selection).unwrap_or(0);
+    match ctx.key {
+        None => MainMenuResult::NoSelection { selected: selection },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => MainMenuResult::Selected { selected: MainMenuSelection::Quit },
+            VirtualKeyCode::Up | VirtualKeyCode::K => {
+                let prev = (current + entries.len() - 1) % entries.len();
+                MainMenuResult::NoSelection { selected: entries[prev].0 }
+            }
+            VirtualKeyCode::Down | VirtualKeyCode::J => {
+                let next = (current + 1) % entries.len();
+                MainMenuResult::NoSelection { selected: entries[next].0 }
+            }
+            VirtualKeyCode::Return => MainMenuResult::Selected { selected: entries[current].0 },
+            _ => MainMenuResult::NoSelection { selected: selection },
+        },
+    }
+}
+
+pub fn game_over(ecs: &World, ctx: &mut Rltk) -> GameOverResult {
+    let stats = ecs.fetch::<GameStats>();
+
+    ctx.print_color_centered(15, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Your journey has ended!");
+    let cause = match &stats.last_hit_by {
+        Some(name) => format!("You were slain by {}.", name),
+        None => "You died.".to_string(),
+    };
+    ctx.print_color_centered(17, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), cause);
+    ctx.print_color_centered(
+        18,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!("You survived {} turns.", stats.turns),
+    );
+
+    ctx.print_color_centered(20, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Press any key to return to the menu.");
+
+    match ctx.key {
+        None => GameOverResult::NoSelection,
+        Some(_) => GameOverResult::QuitToMenu,
+    }
+}
@@ -0,0 +1,302 @@
+use super::{
+    blast_area, blast_centre, line_of_fire, AreaOfEffect, CombatStats, Consumable, Equippable, Equipped, GameLog,
+    GameStats, InPack, InflictsDamage, InflictsStatus, LogEntry, Map, Name, ParticleBuilder, Perk, Perks, Position,
+    ProvidesHealing, Ranged, StatusEffects, StatusKind, SufferDamage, WantsToDropItem, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem,
+};
+use rltk::{Point, RGB};
+use specs::prelude::*;
+
+pub struct ItemCollectionSystem {}
+
+impl<'a> System<'a> for ItemCollectionSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToPickupItem>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, InPack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, mut wants_pickup, mut positions, names, mut backpack) = data;
+
+        for pickup in wants_pickup.join() {
+            // off the floor and into the pack, MapIndexingSystem forgets it next run
+            positions.remove(pickup.item);
+            backpack
+                .insert(pickup.item, InPack { owner: pickup.collected_by })
+                .expect("unable to insert backpack entry");
+
+            if pickup.collected_by == *player_entity {
+                log.push(LogEntry::new("You", "pick up the").target(&names.get(pickup.item).unwrap().name));
+            }
+        }
+
+        wants_pickup.clear();
+    }
+}
+
+pub struct ItemDropSystem {}
+
+impl<'a> System<'a> for ItemDropSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToDropItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InPack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, entities, mut wants_drop, names, mut positions, mut backpack) = data;
+
+        for (ent, to_drop) in (&entities, &wants_drop).join() {
+            let drop_pos = match positions.get(ent) {
+                Some(pos) => Position { x: pos.x, y: pos.y },
+                None => continue,
+            };
+            positions.insert(to_drop.item, drop_pos).expect("unable to insert position");
+            backpack.remove(to_drop.item);
+
+            if ent == *player_entity {
+                log.push(LogEntry::new("You", "drop the").target(&names.get(to_drop.item).unwrap().name));
+            }
+        }
+
+        wants_drop.clear();
+    }
+}
+
+pub struct ItemUseSystem {}
+
+impl<'a> System<'a> for ItemUseSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Consumable>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, InflictsDamage>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InPack>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Ranged>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, AreaOfEffect>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, GameStats>,
+        ReadStorage<'a, Perks>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut log,
+            map,
+            entities,
+            mut wants_use,
+            names,
+            consumables,
+            healing,
+            inflict_damage,
+            mut combat_stats,
+            mut suffer_damage,
+            equippable,
+            mut equipped,
+            mut backpack,
+            inflict_status,
+            mut statuses,
+            ranged,
+            positions,
+            aoe,
+            mut particles,
+            mut stats,
+            perks,
+        ) = data;
+
+        for (user, useitem) in (&entities, &wants_use).join() {
+            let item_name = &names.get(useitem.item).unwrap().name;
+
+            // aimed items fly like arrows, the first creature or wall in the way takes the hit
+            let target = match (useitem.target, ranged.get(useitem.item), positions.get(user)) {
+                (Some(target), Some(_), Some(pos)) => {
+                    let from = Point::new(pos.x, pos.y);
+                    let is_creature = |ent| combat_stats.get(ent).is_some();
+                    if aoe.get(useitem.item).is_some() {
+                        Some(blast_centre(&map, from, target, is_creature))
+                    } else {
+                        line_of_fire(&map, from, target, is_creature).last().copied()
+                    }
+                }
+                (target, _, _) => target,
+            };
+
+            // a blast covers everything around its centre, friend or foe
+            let affected = match (target, aoe.get(useitem.item)) {
+                (Some(target), Some(area)) => {
+                    let tiles = blast_area(&map, target, area.radius);
+                    for tile in tiles.iter() {
+                        particles.request(
+                            tile.x,
+                            tile.y,
+                            RGB::named(rltk::ORANGE),
+                            RGB::named(rltk::BLACK),
+                            rltk::to_cp437('░'),
+                            200.0,
+                        );
+                    }
+                    tiles
+                }
+                (Some(target), None) => vec![target],
+                (None, _) => Vec::new(),
+            };
+            let creatures = affected
+                .iter()
+                .flat_map(|tile| map.tile_content[map.xy_idx(tile.x, tile.y)].iter())
+                .filter(|mob| combat_stats.get(**mob).is_some())
+                .copied()
+                .collect::<Vec<_>>();
+            let user_name = if user == *player_entity {
+                "You".to_string()
+            } else {
+                names.get(user).map(|n| n.name.clone()).unwrap_or_default()
+            };
+
+            if let Some(healer) = healing.get(useitem.item) {
+                if let Some(stats) = combat_stats.get_mut(user) {
+                    let healed = healer.heal_amount.min(stats.max_hp - stats.hp);
+                    stats.hp += healed;
+                    log.push(
+                        LogEntry::new(&user_name, format!("drink the {}, healing", item_name))
+                            .amount(healed)
+                            .color(RGB::named(rltk::GREEN)),
+                    );
+                }
+            }
+
+            // the item is spent either way, at least say where it went
+            let harmful = inflict_damage.get(useitem.item).is_some() || inflict_status.get(useitem.item).is_some();
+            if target.is_some() && harmful && creatures.is_empty() {
+                log.push(LogEntry::new(format!("The {}", item_name), "hits nothing").color(RGB::named(rltk::GREY)));
+            }
+
+            if let Some(damage) = inflict_damage.get(useitem.item) {
+                for mob in creatures.iter() {
+                    SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage, Some(user));
+                    if *mob == *player_entity {
+                        stats.last_hit_by = Some(item_name.clone());
+                    }
+                    let mob_name = if *mob == *player_entity {
+                        "yourself".to_string()
+                    } else {
+                        names.get(*mob).map(|n| n.name.clone()).unwrap_or_default()
+                    };
+                    log.push(
+                        LogEntry::new(&user_name, format!("use {} on", item_name))
+                            .target(mob_name)
+                            .amount(damage.damage)
+                            .color(RGB::named(rltk::ORANGE)),
+                    );
+                }
+            }
+
+            if let Some(inflicts) = inflict_status.get(useitem.item) {
+                // aimed items affect whoever they hit, the rest their user
+                let victims = match target {
+                    Some(_) => creatures.clone(),
+                    None => vec![user],
+                };
+                for victim in victims {
+                    let victim_name = if victim == *player_entity {
+                        "You".to_string()
+                    } else {
+                        names.get(victim).map(|n| n.name.clone()).unwrap_or_default()
+                    };
+                    let hardy = perks.get(victim).is_some_and(|taken| taken.has(Perk::Hardy));
+                    if inflicts.effect.kind == StatusKind::Poison && hardy {
+                        let verb = if victim == *player_entity { "shrug off" } else { "shrugs off" };
+                        log.push(LogEntry::new(victim_name, verb).target("the poison"));
+                        continue;
+                    }
+                    StatusEffects::add(&mut statuses, victim, inflicts.effect);
+                    let verb = if victim == *player_entity { "are" } else { "is" };
+                    log.push(
+                        LogEntry::new(victim_name, format!("{} {}", verb, inflicts.effect.kind.describe().to_lowercase()))
+                            .color(RGB::named(rltk::MAGENTA)),
+                    );
+                }
+            }
+
+            if let Some(can_equip) = equippable.get(useitem.item) {
+                let slot = can_equip.slot;
+
+                // whatever already sits in the slot goes back into the pack
+                let to_unequip = (&entities, &equipped)
+                    .join()
+                    .filter(|(_ent, worn)| worn.owner == user && worn.slot == slot)
+                    .map(|(ent, _worn)| ent)
+                    .collect::<Vec<_>>();
+                for item in to_unequip {
+                    equipped.remove(item);
+                    backpack.insert(item, InPack { owner: user }).expect("unable to insert backpack entry");
+                    let name = names.get(item).map(|n| n.name.clone()).unwrap_or_default();
+                    log.push(LogEntry::new(&user_name, "unequip the").target(name));
+                }
+
+                equipped
+                    .insert(useitem.item, Equipped { owner: user, slot })
+                    .expect("unable to insert equipped component");
+                backpack.remove(useitem.item);
+                log.push(LogEntry::new(&user_name, "equip the").target(item_name));
+            }
+
+            if consumables.get(useitem.item).is_some() {
+                entities.delete(useitem.item).expect("unable to delete consumed item");
+            }
+        }
+
+        wants_use.clear();
+    }
+}
+
+pub struct ItemRemoveSystem {}
+
+impl<'a> System<'a> for ItemRemoveSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InPack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, entities, mut wants_remove, names, mut equipped, mut backpack) = data;
+
+        for (ent, to_remove) in (&entities, &wants_remove).join() {
+            equipped.remove(to_remove.item);
+            backpack
+                .insert(to_remove.item, InPack { owner: ent })
+                .expect("unable to insert backpack entry");
+
+            if ent == *player_entity {
+                log.push(LogEntry::new("You", "take off the").target(&names.get(to_remove.item).unwrap().name));
+            }
+        }
+
+        wants_remove.clear();
+    }
+}
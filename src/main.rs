@@ -1,4 +1,15 @@
-use rltk::{GameState, Point, Rltk, RGB}; use specs::prelude::*; mod component; pub use component::*; mod map; pub use map::*;
+use rltk::{GameState, Point, Rltk, RGB};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
+
+mod component;
+pub use component::*;
+
+mod map;
+pub use map::*;
+
+mod camera;
 
 mod player;
 pub use player::*;
@@ -12,50 +23,289 @@ pub use monster_ai_system::*;
 mod map_indexing_system;
 pub use map_indexing_system::*;
 
+mod attack;
+
 mod melee_combat_system;
 pub use melee_combat_system::*;
 
+mod ranged_combat_system;
+pub use ranged_combat_system::*;
+
 mod damage_system;
 pub use damage_system::*;
 
-#[derive(Clone, Copy, PartialEq)]
+mod saveload_system;
+
+mod gui;
+
+mod gamelog;
+pub use gamelog::*;
+
+mod spawner;
+
+mod raws;
+
+mod map_builders;
+
+mod random_table;
+pub use random_table::*;
+
+mod inventory_system;
+pub use inventory_system::*;
+
+mod door_system;
+pub use door_system::*;
+
+mod status_effect_system;
+pub use status_effect_system::*;
+
+mod particle_system;
+pub use particle_system::*;
+
+mod experience;
+
+/// the seed the world rng was created from, kept as a resource so it can be
+/// shown on screen and attached to bug reports
+pub struct Seed(pub u64);
+
+/// turn counter and the last thing that hurt the player,
+/// read by the game over screen
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct GameStats {
+    pub turns: i32,
+    pub last_hit_by: Option<String>,
+}
+
+/// the value following `flag` on the command line, if any
+fn read_arg(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// seed comes from `--seed <n>` on the command line, then from the
+/// ROGUE_SEED environment variable, none means a fresh random one per game
+fn read_seed() -> Option<u64> {
+    read_arg("--seed")
+        .and_then(|s| s.parse().ok())
+        .or_else(|| std::env::var("ROGUE_SEED").ok().and_then(|s| s.parse().ok()))
+}
+
+/// `--builder <name>` forces one map generator for every level, handy when
+/// working on a generator, a path ending in `.txt` plays a hand made level file,
+/// an unknown name falls back to random picks
+fn read_builder() -> Option<String> {
+    let name = read_arg("--builder")?;
+    if map_builders::builder_by_name(&name, 1).is_none() {
+        rltk::console::log(format!("Unknown map builder {}, picking at random", name));
+    }
+    Some(name)
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum RunState {
     AwaitingInput,
     PreRun,
     PlayerTurn,
     MonsterTurn,
+    SaveGame,
+    MainMenu { menu_selection: gui::MainMenuSelection },
+    GameOver,
+    ShowLog { offset: usize },
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    NextLevel,
+    // replaying the map generator's snapshots, debug only
+    MapGeneration,
+    // the player has the xp for a new level and has to pick a reward
+    LevelUp,
+    // entities can't go into a save file, saving always happens from PreRun anyway
+    #[serde(skip)]
+    ShowTargeting { range: i32, target_for: TargetingFor, cursor: Point },
 }
 
+/// what the targeting cursor picks a tile for
+#[derive(Clone, Copy, PartialEq)]
+pub enum TargetingFor {
+    Item(Entity),
+    // the wielded ranged weapon
+    Shot,
+}
+
+/// how long each generation snapshot stays on screen
+const MAPGEN_FRAME_MS: f32 = 60.0;
+
 pub struct State {
     pub ecs: World,
+    // replayed on every new game when given, so a restart keeps the same dungeon
+    pub fixed_seed: Option<u64>,
+    pub forced_builder: Option<String>,
+    // `--show-mapgen` replays every new level's generation before play starts
+    pub show_mapgen: bool,
+    pub mapgen_history: Vec<Map>,
+    pub mapgen_index: usize,
+    pub mapgen_timer: f32,
 }
 
 impl State {
     fn run_systems(&mut self) {
+        // index first, doors opened last run must not block the view this run
+        let mut map_index = MapIndexingSystem {};
+        map_index.run_now(&self.ecs);
+
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
 
         let mut mob = MonsterAI {};
         mob.run_now(&self.ecs);
 
-        let mut map_index = MapIndexingSystem {};
-        map_index.run_now(&self.ecs);
-
         let mut melee = MeleeCombatSystem{};
         melee.run_now(&self.ecs);
 
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+
+        let mut use_items = ItemUseSystem {};
+        use_items.run_now(&self.ecs);
+
+        // after the monsters acted, so a turn of sleep really costs them a turn
+        let mut statuses = StatusEffectSystem {};
+        statuses.run_now(&self.ecs);
+
         let mut damage = DamageSystem{};
         damage.run_now(&self.ecs);
 
+        let mut pickup = ItemCollectionSystem {};
+        pickup.run_now(&self.ecs);
+
+        let mut drop_items = ItemDropSystem {};
+        drop_items.run_now(&self.ecs);
+
+        let mut remove_items = ItemRemoveSystem {};
+        remove_items.run_now(&self.ecs);
+
+        let mut doors = DoorSystem {};
+        doors.run_now(&self.ecs);
+
+        let mut particles = ParticleSpawnSystem {};
+        particles.run_now(&self.ecs);
+
         self.ecs.maintain();
     }
+
+    /// throw the whole world away and start over, the process keeps running
+    fn new_game(&mut self) {
+        self.ecs = new_world();
+        let seed = self
+            .fixed_seed
+            .unwrap_or_else(|| rltk::RandomNumberGenerator::new().next_u64());
+        self.mapgen_history = new_game(&mut self.ecs, seed, self.forced_builder.as_deref(), self.show_mapgen);
+    }
+
+    /// where play goes once a level is generated, through the replay if asked for
+    fn start_level(&mut self) -> RunState {
+        if self.show_mapgen && !self.mapgen_history.is_empty() {
+            self.mapgen_index = 0;
+            self.mapgen_timer = 0.0;
+            RunState::MapGeneration
+        } else {
+            RunState::PreRun
+        }
+    }
+
+    /// everything that isn't the player or carried by them stays on the old level
+    fn entities_to_remove_on_level_change(&self) -> Vec<Entity> {
+        let entities = self.ecs.entities();
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let backpack = self.ecs.read_storage::<InPack>();
+        let equipped = self.ecs.read_storage::<Equipped>();
+
+        entities
+            .join()
+            .filter(|ent| *ent != player_entity)
+            .filter(|ent| backpack.get(*ent).is_none_or(|pack| pack.owner != player_entity))
+            .filter(|ent| equipped.get(*ent).is_none_or(|worn| worn.owner != player_entity))
+            .collect()
+    }
+
+    fn goto_next_level(&mut self) {
+        for target in self.entities_to_remove_on_level_change() {
+            self.ecs.delete_entity(target).expect("unable to delete entity");
+        }
+
+        let depth = self.ecs.fetch::<Map>().depth + 1;
+        let builder = {
+            let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+            map_builders::build_level(depth, &mut rng, self.forced_builder.as_deref(), self.show_mapgen)
+        };
+
+        let map = builder.get_map();
+        spawner::spawn_list(&mut self.ecs, &map, builder.get_spawn_list());
+
+        let (player_x, player_y) = builder.get_starting_position();
+        self.ecs.insert(map);
+        self.mapgen_history = builder.get_snapshot_history();
+        *self.ecs.write_resource::<Point>() = Point::new(player_x, player_y);
+
+        let player_entity = *self.ecs.fetch::<Entity>();
+        if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(player_entity) {
+            pos.x = player_x;
+            pos.y = player_y;
+        }
+        if let Some(viewshed) = self.ecs.write_storage::<ViewShed>().get_mut(player_entity) {
+            viewshed.dirty = true;
+        }
+
+        self.ecs.write_resource::<GameLog>().push(
+            LogEntry::new("You", format!("descend to depth {}", depth)).color(RGB::named(rltk::YELLOW)),
+        );
+    }
+
+    /// a save is only good for one load, it is removed once read; a save that
+    /// fails to load is kept and the current world is left alone
+    fn continue_game(&mut self) -> bool {
+        let mut ecs = new_world();
+        match saveload_system::load_game(&mut ecs) {
+            Ok(()) => {
+                self.ecs = ecs;
+                if let Err(e) = saveload_system::delete_save() {
+                    rltk::console::log(format!("Unable to delete the save: {}", e));
+                }
+                true
+            }
+            Err(e) => {
+                rltk::console::log(format!("Unable to continue: {}", e));
+                false
+            }
+        }
+    }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut Rltk) {
         ctx.cls();
+        particle_system::cull_dead_particles(&mut self.ecs, ctx);
         let mut newrunstate = *self.ecs.fetch::<RunState>();
 
+        // menus own the whole screen, everything else is drawn over the map
+        match newrunstate {
+            RunState::MainMenu { .. } | RunState::GameOver | RunState::ShowLog { .. } => {}
+            RunState::MapGeneration => {
+                if let Some(snapshot) = self.mapgen_history.get(self.mapgen_index) {
+                    camera::render_snapshot(snapshot, *self.ecs.fetch::<Point>(), ctx);
+                }
+            }
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+
         match newrunstate {
             RunState::PreRun => {
                 self.run_systems();
@@ -66,11 +316,152 @@ impl GameState for State {
             },
             RunState::PlayerTurn => {
                 self.run_systems();
+                self.ecs.write_resource::<GameStats>().turns += 1;
                 newrunstate = RunState::MonsterTurn;
             },
             RunState::MonsterTurn => {
                 self.run_systems();
-                newrunstate = RunState::AwaitingInput;
+                newrunstate = if experience::can_level_up(&self.ecs) {
+                    RunState::LevelUp
+                } else {
+                    RunState::AwaitingInput
+                };
+            }
+            RunState::LevelUp => {
+                if let gui::LevelUpResult::Selected { choice } = gui::level_up_menu(&self.ecs, ctx) {
+                    experience::level_up(&mut self.ecs, choice);
+                    // enough xp for two levels at once shows the screen again
+                    if !experience::can_level_up(&self.ecs) {
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::SaveGame => {
+                // resume with a PreRun, it rebuilds what the save file skips
+                *self.ecs.write_resource::<RunState>() = RunState::PreRun;
+                match saveload_system::save_game(&mut self.ecs) {
+                    Ok(()) => {
+                        ctx.quit();
+                        newrunstate = RunState::PreRun;
+                    }
+                    Err(e) => {
+                        // better to keep playing than to quit and lose the run
+                        self.ecs.write_resource::<GameLog>().push(
+                            LogEntry::new("The game", format!("could not be saved: {}", e)).color(RGB::named(rltk::RED)),
+                        );
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::MainMenu { .. } => match gui::main_menu(self, ctx) {
+                gui::MainMenuResult::NoSelection { selected } => {
+                    newrunstate = RunState::MainMenu { menu_selection: selected };
+                }
+                gui::MainMenuResult::Selected { selected } => match selected {
+                    gui::MainMenuSelection::NewGame => {
+                        self.new_game();
+                        newrunstate = self.start_level();
+                    }
+                    gui::MainMenuSelection::LoadGame => {
+                        if self.continue_game() {
+                            newrunstate = *self.ecs.fetch::<RunState>();
+                        } else {
+                            newrunstate = RunState::MainMenu { menu_selection: gui::MainMenuSelection::NewGame };
+                        }
+                    }
+                    gui::MainMenuSelection::Quit => ctx.quit(),
+                },
+            },
+            RunState::ShowLog { offset } => match gui::show_log(&self.ecs, ctx, offset) {
+                gui::LogViewResult::Scrolled { offset } => newrunstate = RunState::ShowLog { offset },
+                gui::LogViewResult::Close => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::ShowInventory => match gui::show_inventory(&self.ecs, ctx, "Inventory") {
+                (gui::ItemMenuResult::Cancel, _) => newrunstate = RunState::AwaitingInput,
+                (gui::ItemMenuResult::NoResponse, _) => {}
+                (gui::ItemMenuResult::Selected, Some(item)) => {
+                    let range = self.ecs.read_storage::<Ranged>().get(item).map(|r| r.range);
+                    match range {
+                        Some(range) => {
+                            let target_for = TargetingFor::Item(item);
+                            let cursor = gui::initial_cursor(&self.ecs, range, target_for);
+                            newrunstate = RunState::ShowTargeting { range, target_for, cursor };
+                        }
+                        None => {
+                            use_item(&mut self.ecs, item, None);
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+                (gui::ItemMenuResult::Selected, None) => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::ShowTargeting { range, target_for, cursor } => {
+                match gui::ranged_target(&self.ecs, ctx, range, target_for, cursor) {
+                    gui::TargetingResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::TargetingResult::NoResponse { cursor } => {
+                        newrunstate = RunState::ShowTargeting { range, target_for, cursor };
+                    }
+                    gui::TargetingResult::Selected { target } => {
+                        match target_for {
+                            TargetingFor::Item(item) => use_item(&mut self.ecs, item, Some(target)),
+                            TargetingFor::Shot => {
+                                let player = *self.ecs.fetch::<Entity>();
+                                self.ecs
+                                    .write_storage::<WantsToShoot>()
+                                    .insert(player, WantsToShoot { target })
+                                    .expect("unable to insert shoot intent");
+                            }
+                        }
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowDropItem => match gui::show_inventory(&self.ecs, ctx, "Drop which item?") {
+                (gui::ItemMenuResult::Cancel, _) => newrunstate = RunState::AwaitingInput,
+                (gui::ItemMenuResult::NoResponse, _) => {}
+                (gui::ItemMenuResult::Selected, Some(item)) => {
+                    let player = *self.ecs.fetch::<Entity>();
+                    self.ecs
+                        .write_storage::<WantsToDropItem>()
+                        .insert(player, WantsToDropItem { item })
+                        .expect("unable to insert drop intent");
+                    newrunstate = RunState::PlayerTurn;
+                }
+                (gui::ItemMenuResult::Selected, None) => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::NextLevel => {
+                self.goto_next_level();
+                newrunstate = self.start_level();
+            }
+            RunState::MapGeneration => {
+                // any key skips the rest of the replay
+                self.mapgen_timer += ctx.frame_time_ms;
+                if self.mapgen_timer > MAPGEN_FRAME_MS {
+                    self.mapgen_timer = 0.0;
+                    self.mapgen_index += 1;
+                }
+                if self.mapgen_index >= self.mapgen_history.len() || ctx.key.is_some() {
+                    self.mapgen_history.clear();
+                    newrunstate = RunState::PreRun;
+                }
+            }
+            RunState::ShowRemoveItem => match gui::remove_item_menu(&self.ecs, ctx) {
+                (gui::ItemMenuResult::Cancel, _) => newrunstate = RunState::AwaitingInput,
+                (gui::ItemMenuResult::NoResponse, _) => {}
+                (gui::ItemMenuResult::Selected, Some(item)) => {
+                    let player = *self.ecs.fetch::<Entity>();
+                    self.ecs
+                        .write_storage::<WantsToRemoveItem>()
+                        .insert(player, WantsToRemoveItem { item })
+                        .expect("unable to insert remove intent");
+                    newrunstate = RunState::PlayerTurn;
+                }
+                (gui::ItemMenuResult::Selected, None) => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::GameOver => {
+                if gui::game_over(&self.ecs, ctx) == gui::GameOverResult::QuitToMenu {
+                    newrunstate = RunState::MainMenu { menu_selection: gui::MainMenuSelection::NewGame };
+                }
             }
         }
 
@@ -88,111 +479,132 @@ impl GameState for State {
         //     self.run_state = RunState::Paused;
         // }
 
-        damage_system::delete_the_dead(&mut self.ecs);
-        draw_map(&self.ecs, ctx);
-
-        let positions = self.ecs.read_storage::<Position>();
-        let renderables = self.ecs.read_storage::<Renderable>();
-        let players = self.ecs.read_storage::<Player>();
-        let viewsheds = self.ecs.read_storage::<ViewShed>();
-
-        for (pos, render) in (&positions, &renderables).join() {
-            let pt = Point::new(pos.x, pos.y);
-            for (_player, viewshed) in (&players, &viewsheds).join() {
-                if viewshed.visible_tiles.contains(&pt) {
-                    ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph);
-                }
-            }
+        if !matches!(newrunstate, RunState::MainMenu { .. } | RunState::GameOver) {
+            damage_system::delete_the_dead(&mut self.ecs);
         }
     }
 }
 
+/// queue an item for ItemUseSystem on the player's behalf
+fn use_item(ecs: &mut World, item: Entity, target: Option<Point>) {
+    let player = *ecs.fetch::<Entity>();
+    ecs.write_storage::<WantsToUseItem>()
+        .insert(player, WantsToUseItem { item, target })
+        .expect("unable to insert intent");
+}
+
 fn main() -> rltk::BError {
     use rltk::RltkBuilder;
 
+    if let Err(e) = raws::load_raws() {
+        rltk::console::log(format!("Unable to load raws: {}", e));
+        std::process::exit(1);
+    }
+
     // a simple terminal monitor with shape 80X50
     let monitor = RltkBuilder::simple80x50()
         .with_title("Hello Rogue")
         .build()?;
 
     // gs ticks every tick, this will set the monitor terminal
-    let mut gs = State {
-        ecs: World::new(),
+    let gs = State {
+        ecs: new_world(),
+        fixed_seed: read_seed(),
+        forced_builder: read_builder(),
+        show_mapgen: std::env::args().any(|arg| arg == "--show-mapgen"),
+        mapgen_history: Vec::new(),
+        mapgen_index: 0,
+        mapgen_timer: 0.0,
     };
 
+    // game main loop, inside monitor, rendering by calling tick
+    rltk::main_loop(monitor, gs)
+}
+
+/// an empty world with every component registered, parked at the main menu
+fn new_world() -> World {
+    let mut ecs = World::new();
+
     // let ecs register component
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<ViewShed>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
-
-    let map = Map::new_map_rooms_and_corridors();
-    let (player_x, player_y) = map.rooms.first().unwrap().center();
-
-    let mut rng = rltk::RandomNumberGenerator::new();
-    for (i, room) in map.rooms.iter().skip(1).enumerate() {
-        let (x, y) = room.center();
-        let roll = rng.roll_dice(1, 2);
-        let (glyph, name) = match roll {
-            1 => (rltk::to_cp437('g'), "Goblin".to_string()),
-            _ => (rltk::to_cp437('o'), "Orc".to_string()),
-        };
-        gs.ecs
-            .create_entity()
-            .with(Position { x, y })
-            .with(Renderable {
-                glyph,
-                fg: RGB::named(rltk::RED),
-                bg: RGB::named(rltk::BLACK),
-            })
-            .with(ViewShed {
-                visible_tiles: Vec::new(),
-                range: 8,
-                dirty: true,
-            })
-            .with(Monster {})
-            .with(BlocksTile {})
-            .with(Name {
-                name: format!("{} #{}", name, i),
-            })
-            .with(CombatStats {max_hp: 16, hp: 16, defense: 1, power: 4})
-            .build();
-    }
+    ecs.register::<Position>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<ViewShed>();
+    ecs.register::<Monster>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<CombatStats>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<SufferDamage>();
+    ecs.register::<Item>();
+    ecs.register::<InPack>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<Consumable>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<Ranged>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleePowerBonus>();
+    ecs.register::<DefenseBonus>();
+    ecs.register::<MeleeWeapon>();
+    ecs.register::<NaturalAttack>();
+    ecs.register::<StatusEffects>();
+    ecs.register::<InflictsStatus>();
+    ecs.register::<RangedWeapon>();
+    ecs.register::<Ammunition>();
+    ecs.register::<WantsToShoot>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<ParticleLifetime>();
+    ecs.register::<Experience>();
+    ecs.register::<GrantsExperience>();
+    ecs.register::<Perks>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<BlocksVisibility>();
+    ecs.register::<Door>();
+    ecs.register::<WantsToOpenDoor>();
+    ecs.register::<WantsToCloseDoor>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+    ecs.insert(GameLog::default());
+    ecs.insert(ParticleBuilder::default());
+    ecs.insert(RunState::MainMenu { menu_selection: gui::MainMenuSelection::NewGame });
+
+    ecs
+}
+
+/// returns the generation snapshots of the first level for the visualiser,
+/// empty unless `record_history` asked for them
+fn new_game(ecs: &mut World, seed: u64, forced_builder: Option<&str>, record_history: bool) -> Vec<Map> {
+    // every random roll in the game goes through this one seeded rng,
+    // so the same seed always rebuilds the same dungeon
+    rltk::console::log(format!("Seed: {}", seed));
+    let mut rng = rltk::RandomNumberGenerator::seeded(seed);
+
+    let builder = map_builders::build_level(1, &mut rng, forced_builder, record_history);
+    let map = builder.get_map();
+    let (player_x, player_y) = builder.get_starting_position();
 
     //let ecs register resource
-    gs.ecs.insert(map);
-    gs.ecs.insert(Point::new(player_x, player_y));
-    gs.ecs.insert(RunState::PreRun);
+    ecs.insert(rng);
+    ecs.insert(Seed(seed));
+    ecs.insert(GameStats::default());
+    ecs.insert(Point::new(player_x, player_y));
+    ecs.insert(RunState::PreRun);
+    ecs.write_resource::<GameLog>().push(
+        LogEntry::new("You", "enter the dungeon").color(RGB::named(rltk::YELLOW)),
+    );
+
+    spawner::spawn_list(ecs, &map, builder.get_spawn_list());
+    ecs.insert(map);
 
     // before main loop, create all the entity
-    let player_ent = gs.ecs
-        .create_entity()
-        .with(Position {
-            x: player_x,
-            y: player_y,
-        })
-        .with(Renderable {
-            glyph: rltk::to_cp437('@'),
-            fg: RGB::named(rltk::YELLOW),
-            bg: RGB::named(rltk::BLACK),
-        })
-        .with(Player {})
-        .with(Name {name: "Neil".to_string()})
-        .with(ViewShed {
-            visible_tiles: Vec::new(),
-            range: 8,
-            dirty: true,
-        })
-        .with(CombatStats { max_hp: 30, hp: 30, defense: 2, power: 5})
-        .build();
-    
-    gs.ecs.insert(player_ent);
-    // game main loop, inside monitor, rendering by calling tick
-    rltk::main_loop(monitor, gs)
+    let player_ent = spawner::player(ecs, player_x, player_y);
+    ecs.insert(player_ent);
+
+    builder.get_snapshot_history()
 }
@@ -1,19 +1,17 @@
-use super::{Player, ViewShed};
-use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator, Rltk, RGB};
+use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
-use specs::{World, WorldExt};
 use std::cmp::{max, min};
+use std::collections::HashSet;
 
 // use super::{Rect};
 
-pub const WIDTH: usize = 80;
-pub const HEIGHT: usize = 50;
-
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Rect {
-    x1: i32,
-    y1: i32,
-    x2: i32,
-    y2: i32,
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
 }
 
 impl Rect {
@@ -35,13 +33,14 @@ impl Rect {
     }
 }
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
     pub tiles: Vec<TileType>,
     pub rooms: Vec<Rect>,
@@ -49,7 +48,25 @@ pub struct Map {
     pub height: i32,
     pub revealed_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
-    pub tile_content: Vec<Vec<Entity>>
+    pub depth: i32,
+
+    // rebuilt by MapIndexingSystem every turn, no point saving it
+    #[serde(skip_serializing, skip_deserializing)]
+    pub tile_content: Vec<Vec<Entity>>,
+
+    // also rebuilt by MapIndexingSystem, tiles with something opaque or a closed door on them
+    #[serde(skip_serializing, skip_deserializing)]
+    pub view_blocked: HashSet<usize>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub closed_doors: HashSet<usize>,
+
+    // the tiles after every step of generation, replayed by the visualiser
+    #[serde(skip_serializing, skip_deserializing)]
+    pub history: Vec<Vec<TileType>>,
+    // snapshots are only taken when the visualiser will show them, big
+    // levels would otherwise copy their tiles thousands of times
+    #[serde(skip_serializing, skip_deserializing)]
+    pub record_history: bool,
 }
 
 impl Map {
@@ -61,38 +78,68 @@ impl Map {
         (idx as i32 % self.width, idx as i32 / self.width)
     }
 
+    /// anywhere but the outermost ring, which always stays wall
+    fn is_inside_border(&self, x: i32, y: i32) -> bool {
+        x >= 1 && x < self.width - 1 && y >= 1 && y < self.height - 1
+    }
+
+    /// carve a floor tile, silently ignored on or past the border
+    fn carve(&mut self, x: i32, y: i32) {
+        if self.is_inside_border(x, y) {
+            let idx = self.xy_idx(x, y);
+            self.tiles[idx] = TileType::Floor;
+        }
+    }
+
     pub fn apply_room_to_map(&mut self, room: &Rect) {
         for y in room.y1 + 1..=room.y2 {
             for x in room.x1 + 1..=room.x2 {
-                let idx = self.xy_idx(x, y);
-                self.tiles[idx] = TileType::Floor;
+                self.carve(x, y);
             }
         }
+        self.take_snapshot();
     }
 
     pub fn apply_horizontal_tunnel(&mut self, x1: i32, x2: i32, y: i32) {
         for x in min(x1, x2)..=max(x1, x2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < WIDTH * HEIGHT {
-                self.tiles[idx] = TileType::Floor;
-            }
+            self.carve(x, y);
         }
+        self.take_snapshot();
     }
 
     pub fn apply_vertical_tunnel(&mut self, y1: i32, y2: i32, x: i32) {
         for y in min(y1, y2)..=max(y1, y2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < WIDTH * HEIGHT {
-                self.tiles[idx] = TileType::Floor;
-            }
+            self.carve(x, y);
+        }
+        self.take_snapshot();
+    }
+
+    /// remember the tiles as they are now for the generation visualiser, if it is on
+    pub fn take_snapshot(&mut self) {
+        if self.record_history {
+            self.history.push(self.tiles.clone());
+        }
+    }
+
+    /// join two points with an L shaped corridor, a coin picks which leg goes first
+    pub fn apply_l_tunnel(&mut self, rng: &mut RandomNumberGenerator, from: (i32, i32), to: (i32, i32)) {
+        let ((x1, y1), (x2, y2)) = (from, to);
+        if rng.range(0, 2) == 1 {
+            self.apply_horizontal_tunnel(x1, x2, y1);
+            self.apply_vertical_tunnel(y1, y2, x2);
+        } else {
+            self.apply_vertical_tunnel(y1, y2, x1);
+            self.apply_horizontal_tunnel(x1, x2, y2);
         }
     }
 
+    /// closed doors count as open ground for pathing, whoever walks into one opens it
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
         if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
             return false;
         }
-        !self.blocked[self.xy_idx(x, y)]
+        let idx = self.xy_idx(x, y);
+        !self.blocked[idx] || self.closed_doors.contains(&idx)
     }
 
     pub fn clear_all_content(&mut self) {
@@ -101,50 +148,23 @@ impl Map {
         }
     }
 
-    pub fn new_map_rooms_and_corridors() -> Map {
-        let mut map = Map {
-            tiles: vec![TileType::Wall; WIDTH * HEIGHT],
+    /// solid rock, every generator carves out of this
+    pub fn new(depth: i32, width: i32, height: i32) -> Map {
+        let size = (width * height) as usize;
+        Map {
+            tiles: vec![TileType::Wall; size],
             rooms: Vec::new(),
-            width: WIDTH as i32,
-            height: HEIGHT as i32,
-            revealed_tiles: vec![false; WIDTH * HEIGHT],
-            blocked: vec![false; WIDTH * HEIGHT],
-            tile_content: vec![Vec::new(); WIDTH * HEIGHT],
-        };
-
-        const MAX_ROOMS: i32 = 30;
-        const MIN_SIZE: i32 = 6;
-        const MAX_SIZE: i32 = 10;
-
-        let mut rng = RandomNumberGenerator::new();
-
-        for _ in 0..MAX_ROOMS {
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-            // x max is 80 - 1 - 1 - w
-            let x = rng.roll_dice(1, 80 - 1 - w) - 1;
-            let y = rng.roll_dice(1, 50 - 1 - h) - 1;
-            let new_room = Rect::new(x, y, w, h);
-            if !map.rooms.iter().any(|other| new_room.intersect(other)) {
-                // apply_room_to_map(&new_room, &mut map);
-                map.apply_room_to_map(&new_room);
-                if !map.rooms.is_empty() {
-                    let (x1, y1) = new_room.center();
-                    let (x2, y2) = map.rooms.last().unwrap().center();
-
-                    if rng.range(0, 2) == 1 {
-                        map.apply_horizontal_tunnel(x1, x2, y1);
-                        map.apply_vertical_tunnel(y1, y2, x2);
-                    } else {
-                        map.apply_vertical_tunnel(y1, y2, x1);
-                        map.apply_horizontal_tunnel(x1, x2, y2);
-                    }
-                }
-                map.rooms.push(new_room);
-            }
+            width,
+            height,
+            revealed_tiles: vec![false; size],
+            blocked: vec![false; size],
+            depth,
+            tile_content: vec![Vec::new(); size],
+            view_blocked: HashSet::new(),
+            closed_doors: HashSet::new(),
+            history: Vec::new(),
+            record_history: false,
         }
-
-        map
     }
 
     pub fn populates_blocked(&mut self) {
@@ -162,7 +182,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx] == TileType::Wall
+        self.tiles[idx] == TileType::Wall || self.view_blocked.contains(&idx)
     }
 
     fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
@@ -203,36 +223,3 @@ impl BaseMap for Map {
         rltk::DistanceAlg::Pythagoras.distance2d(p1, p2)
     }
 }
-
-pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
-    let mut viewsheds = ecs.write_storage::<ViewShed>();
-    let mut players = ecs.write_storage::<Player>();
-    let map = ecs.fetch::<Map>();
-
-    for (_player, viewshed) in (&mut players, &mut viewsheds).join() {
-        for (idx, tile) in map.tiles.iter().enumerate() {
-            let (x, y) = map.idx_xy(idx);
-            let pt = Point::new(x, y);
-
-            if map.revealed_tiles[idx] {
-                let glyph;
-                let mut fg;
-                match tile {
-                    TileType::Wall => {
-                        glyph = rltk::to_cp437('#');
-                        fg = RGB::from_f32(0., 1.0, 0.);
-                    }
-                    TileType::Floor => {
-                        glyph = rltk::to_cp437('.');
-                        fg = RGB::from_f32(0., 0.5, 0.5);
-                    }
-                }
-
-                if !viewshed.visible_tiles.contains(&pt) {
-                    fg = fg.to_greyscale()
-                }
-                ctx.set(x, y, fg, RGB::from_f32(0., 0., 0.), glyph);
-            }
-        }
-    }
-}
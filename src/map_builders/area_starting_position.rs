@@ -0,0 +1,25 @@
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// start in the middle of the map, walking left until there is floor
+pub struct CentreStartingPosition {}
+
+impl MetaMapBuilder for CentreStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let (mut start_x, start_y) = (map.width / 2, map.height / 2);
+        while map.tiles[map.xy_idx(start_x, start_y)] != TileType::Floor && start_x > 1 {
+            start_x -= 1;
+        }
+        // solid all the way across, dig the start out rather than fail
+        let start_idx = map.xy_idx(start_x, start_y);
+        map.tiles[start_idx] = TileType::Floor;
+        build_data.starting_position = Some((start_x, start_y));
+    }
+}
+
+impl CentreStartingPosition {
+    pub fn new() -> Box<CentreStartingPosition> {
+        Box::new(CentreStartingPosition {})
+    }
+}
@@ -0,0 +1,91 @@
+use super::{BuilderMap, InitialMapBuilder, Map, Rect};
+use rltk::RandomNumberGenerator;
+
+/// rooms from binary space partition: split the level in two until the
+/// pieces are small, put a room in every piece, then join the halves of
+/// every split with a corridor on the way back up
+pub struct BspDungeonBuilder {}
+
+impl InitialMapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        // rooms carve from x1 + 1 to x2, this keeps the outermost tiles solid
+        let area = Rect::new(0, 0, map.width - 1, map.height - 1);
+        partition(map, &area, rng);
+    }
+}
+
+impl BspDungeonBuilder {
+    pub fn new() -> Box<BspDungeonBuilder> {
+        Box::new(BspDungeonBuilder {})
+    }
+}
+
+/// returns the index of one room inside `area`, the parent connects to it
+fn partition(map: &mut Map, area: &Rect, rng: &mut RandomNumberGenerator) -> usize {
+    const MIN_LEAF: i32 = 8;
+    const MAX_LEAF: i32 = 20;
+
+    let w = area.x2 - area.x1;
+    let h = area.y2 - area.y1;
+    let can_split_x = w >= MIN_LEAF * 2;
+    let can_split_y = h >= MIN_LEAF * 2;
+
+    // small enough pieces stop splitting now and then, so rooms vary in size
+    let small = w <= MAX_LEAF && h <= MAX_LEAF;
+    if !(can_split_x || can_split_y) || (small && rng.roll_dice(1, 3) == 1) {
+        return leaf_room(map, area, rng);
+    }
+
+    // cut across the longer side, a coin decides for squarish pieces
+    let split_x = if !can_split_y {
+        true
+    } else if !can_split_x {
+        false
+    } else if w as f32 > h as f32 * 1.25 {
+        true
+    } else if h as f32 > w as f32 * 1.25 {
+        false
+    } else {
+        rng.roll_dice(1, 2) == 1
+    };
+
+    let (first, second) = if split_x {
+        let at = rng.range(MIN_LEAF, w - MIN_LEAF + 1);
+        (Rect::new(area.x1, area.y1, at, h), Rect::new(area.x1 + at, area.y1, w - at, h))
+    } else {
+        let at = rng.range(MIN_LEAF, h - MIN_LEAF + 1);
+        (Rect::new(area.x1, area.y1, w, at), Rect::new(area.x1, area.y1 + at, w, h - at))
+    };
+
+    let first_room = partition(map, &first, rng);
+    let second_room = partition(map, &second, rng);
+
+    let from = map.rooms[first_room].center();
+    let to = map.rooms[second_room].center();
+    map.apply_l_tunnel(rng, from, to);
+
+    if rng.roll_dice(1, 2) == 1 {
+        first_room
+    } else {
+        second_room
+    }
+}
+
+/// a room somewhere inside the leaf, the leaf's far edge stays wall so
+/// neighbouring leaves never merge
+fn leaf_room(map: &mut Map, area: &Rect, rng: &mut RandomNumberGenerator) -> usize {
+    const MIN_ROOM: i32 = 4;
+
+    let w = area.x2 - area.x1;
+    let h = area.y2 - area.y1;
+    let room_w = rng.range(MIN_ROOM, w);
+    let room_h = rng.range(MIN_ROOM, h);
+    let x = area.x1 + rng.range(0, w - room_w);
+    let y = area.y1 + rng.range(0, h - room_h);
+
+    let room = Rect::new(x, y, room_w, room_h);
+    map.apply_room_to_map(&room);
+    map.rooms.push(room);
+    map.rooms.len() - 1
+}
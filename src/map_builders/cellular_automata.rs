@@ -0,0 +1,56 @@
+use super::{BuilderMap, InitialMapBuilder, Map, TileType};
+use rltk::RandomNumberGenerator;
+
+/// caves: noise smoothed into blobs, needs culling afterwards since
+/// nothing guarantees the blobs touch
+pub struct CellularAutomataBuilder {}
+
+impl InitialMapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        const ITERATIONS: usize = 15;
+
+        let map = &mut build_data.map;
+
+        // 55% floor noise, the border stays rock
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = if rng.roll_dice(1, 100) > 55 { TileType::Floor } else { TileType::Wall };
+            }
+        }
+        build_data.take_snapshot();
+
+        // crowded tiles and lonely tiles turn to rock, everything else opens up
+        for _ in 0..ITERATIONS {
+            let map = &mut build_data.map;
+            let mut next = map.tiles.clone();
+            for y in 1..map.height - 1 {
+                for x in 1..map.width - 1 {
+                    let walls = count_neighbour_walls(map, x, y);
+                    let idx = map.xy_idx(x, y);
+                    next[idx] = if walls > 4 || walls == 0 { TileType::Wall } else { TileType::Floor };
+                }
+            }
+            map.tiles = next;
+            build_data.take_snapshot();
+        }
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new() -> Box<CellularAutomataBuilder> {
+        Box::new(CellularAutomataBuilder {})
+    }
+}
+
+fn count_neighbour_walls(map: &Map, x: i32, y: i32) -> usize {
+    let mut walls = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if (dx, dy) != (0, 0) && map.tiles[map.xy_idx(x + dx, y + dy)] == TileType::Wall {
+                walls += 1;
+            }
+        }
+    }
+    walls
+}
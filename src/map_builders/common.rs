@@ -0,0 +1,59 @@
+use super::{Map, TileType};
+
+/// walking distance from `start_idx` to every tile, f32::MAX where it can't
+/// be reached; `blocked` is refreshed first since the search walks on it
+pub fn distances_from_start(map: &mut Map, start_idx: usize) -> rltk::DijkstraMap {
+    map.populates_blocked();
+    // no depth limit, the far end of a 200x120 level is well past any fixed one
+    let max_depth = (map.width * map.height) as f32;
+    rltk::DijkstraMap::new(map.width, map.height, &[start_idx], &*map, max_depth)
+}
+
+/// mirror every tile painted around the centre line(s) of the map
+#[derive(PartialEq, Copy, Clone)]
+pub enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+/// carve floor at x, y with a square brush, mirrored as asked,
+/// the outermost ring of the map is never touched, returns how many walls became floor
+pub fn paint(map: &mut Map, symmetry: Symmetry, brush_size: i32, x: i32, y: i32) -> usize {
+    let (center_x, center_y) = (map.width / 2, map.height / 2);
+    let mirror = |value: i32, center: i32| [center - (center - value).abs(), center + (center - value).abs()];
+
+    let xs = match symmetry {
+        Symmetry::Horizontal | Symmetry::Both => mirror(x, center_x),
+        _ => [x, x],
+    };
+    let ys = match symmetry {
+        Symmetry::Vertical | Symmetry::Both => mirror(y, center_y),
+        _ => [y, y],
+    };
+
+    let mut carved = 0;
+    for &py in ys.iter() {
+        for &px in xs.iter() {
+            carved += apply_brush(map, brush_size, px, py);
+        }
+    }
+    carved
+}
+
+fn apply_brush(map: &mut Map, brush_size: i32, x: i32, y: i32) -> usize {
+    let mut carved = 0;
+    for brush_y in y..y + brush_size {
+        for brush_x in x..x + brush_size {
+            if brush_x > 0 && brush_x < map.width - 1 && brush_y > 0 && brush_y < map.height - 1 {
+                let idx = map.xy_idx(brush_x, brush_y);
+                if map.tiles[idx] != TileType::Floor {
+                    map.tiles[idx] = TileType::Floor;
+                    carved += 1;
+                }
+            }
+        }
+    }
+    carved
+}
@@ -0,0 +1,27 @@
+use super::common::distances_from_start;
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// fill in every floor tile the starting position can't walk to
+pub struct CullUnreachable {}
+
+impl MetaMapBuilder for CullUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let (start_x, start_y) = build_data.starting_position.expect("culling needs a starting position");
+        let map = &mut build_data.map;
+        let start_idx = map.xy_idx(start_x, start_y);
+        let dijkstra = distances_from_start(map, start_idx);
+        for (idx, tile) in map.tiles.iter_mut().enumerate() {
+            if *tile == TileType::Floor && dijkstra.map[idx] == f32::MAX {
+                *tile = TileType::Wall;
+            }
+        }
+        map.populates_blocked();
+    }
+}
+
+impl CullUnreachable {
+    pub fn new() -> Box<CullUnreachable> {
+        Box::new(CullUnreachable {})
+    }
+}
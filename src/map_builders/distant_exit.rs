@@ -0,0 +1,29 @@
+use super::common::distances_from_start;
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// the stairs go on the reachable tile farthest from the start
+pub struct DistantExit {}
+
+impl MetaMapBuilder for DistantExit {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let (start_x, start_y) = build_data.starting_position.expect("distant exit needs a starting position");
+        let map = &mut build_data.map;
+        let start_idx = map.xy_idx(start_x, start_y);
+        let dijkstra = distances_from_start(map, start_idx);
+        let mut exit = (start_idx, 0.0);
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            let distance = dijkstra.map[idx];
+            if *tile == TileType::Floor && distance != f32::MAX && distance > exit.1 {
+                exit = (idx, distance);
+            }
+        }
+        map.tiles[exit.0] = TileType::DownStairs;
+    }
+}
+
+impl DistantExit {
+    pub fn new() -> Box<DistantExit> {
+        Box::new(DistantExit {})
+    }
+}
@@ -0,0 +1,176 @@
+use super::common::{paint, Symmetry};
+use super::{BuilderMap, InitialMapBuilder, Map, TileType};
+use rltk::{Point, RandomNumberGenerator};
+
+/// how the particles find their way to the existing floor
+#[derive(PartialEq, Copy, Clone)]
+pub enum DlaAlgorithm {
+    // stumble about at random until they bump into floor
+    WalkInwards,
+    // head straight for the centre
+    CentralAttractor,
+}
+
+#[derive(Copy, Clone)]
+pub struct DlaSettings {
+    pub algorithm: DlaAlgorithm,
+    pub brush_size: i32,
+    pub symmetry: Symmetry,
+    // stop dropping particles once this much of the map is floor
+    pub floor_percent: f32,
+}
+
+/// diffusion-limited aggregation: particles dropped anywhere on the map move
+/// towards the dug area and stick where they first touch it
+pub struct DlaBuilder {
+    settings: DlaSettings,
+}
+
+impl InitialMapBuilder for DlaBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        const PARTICLES_PER_SNAPSHOT: i32 = 20;
+
+        let settings = self.settings;
+        let map = &mut build_data.map;
+        let (center_x, center_y) = (map.width / 2, map.height / 2);
+
+        // a small cross in the middle for the first particles to stick to
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let idx = map.xy_idx(center_x + dx, center_y + dy);
+            map.tiles[idx] = TileType::Floor;
+        }
+
+        let desired_floor = (settings.floor_percent * map.tiles.len() as f32) as usize;
+        let mut floor_count = map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        let mut particles = 0;
+        let mut bounds = walk_bounds(map);
+        while floor_count < desired_floor {
+            // random walkers stay near what has grown so far, a walker dropped
+            // in a far corner of a big map takes ages to arrive anywhere
+            let (min_x, min_y, max_x, max_y) = match settings.algorithm {
+                DlaAlgorithm::WalkInwards => bounds,
+                DlaAlgorithm::CentralAttractor => (1, 1, map.width - 2, map.height - 2),
+            };
+            let mut x = rng.range(min_x, max_x + 1);
+            let mut y = rng.range(min_y, max_y + 1);
+            if map.tiles[map.xy_idx(x, y)] == TileType::Floor {
+                continue;
+            }
+            let (mut prev_x, mut prev_y) = (x, y);
+
+            match settings.algorithm {
+                DlaAlgorithm::WalkInwards => {
+                    while map.tiles[map.xy_idx(x, y)] == TileType::Wall {
+                        prev_x = x;
+                        prev_y = y;
+                        match rng.roll_dice(1, 4) {
+                            1 if x > min_x => x -= 1,
+                            2 if x < max_x => x += 1,
+                            3 if y > min_y => y -= 1,
+                            4 if y < max_y => y += 1,
+                            _ => {}
+                        }
+                    }
+                }
+                DlaAlgorithm::CentralAttractor => {
+                    // the line ends on the centre, which is floor, so this always stops
+                    let path = rltk::line2d(rltk::LineAlg::Bresenham, Point::new(x, y), Point::new(center_x, center_y));
+                    for step in path {
+                        if map.tiles[map.xy_idx(step.x, step.y)] != TileType::Wall {
+                            break;
+                        }
+                        prev_x = step.x;
+                        prev_y = step.y;
+                    }
+                }
+            }
+
+            floor_count += paint(map, settings.symmetry, settings.brush_size, prev_x, prev_y);
+
+            particles += 1;
+            if particles % PARTICLES_PER_SNAPSHOT == 0 {
+                map.take_snapshot();
+                bounds = walk_bounds(map);
+            }
+        }
+    }
+}
+
+/// the box around all floor so far with some room to spare, inside the border:
+/// (min_x, min_y, max_x, max_y), max inclusive
+fn walk_bounds(map: &Map) -> (i32, i32, i32, i32) {
+    const MARGIN: i32 = 8;
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (map.width, map.height, 0, 0);
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if *tile == TileType::Floor {
+            let (x, y) = map.idx_xy(idx);
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    (
+        (min_x - MARGIN).max(1),
+        (min_y - MARGIN).max(1),
+        (max_x + MARGIN).min(map.width - 2),
+        (max_y + MARGIN).min(map.height - 2),
+    )
+}
+
+impl DlaBuilder {
+    pub fn new(settings: DlaSettings) -> Box<DlaBuilder> {
+        Box::new(DlaBuilder { settings })
+    }
+
+    /// thin branching tunnels, like frost on glass
+    pub fn walk_inwards() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings {
+            algorithm: DlaAlgorithm::WalkInwards,
+            brush_size: 1,
+            symmetry: Symmetry::None,
+            floor_percent: 0.25,
+        })
+    }
+
+    /// spokes running out from the middle
+    pub fn central_attractor() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            brush_size: 2,
+            symmetry: Symmetry::None,
+            floor_percent: 0.25,
+        })
+    }
+
+    /// wide tunnels mirrored left to right and top to bottom
+    pub fn insectoid() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            brush_size: 2,
+            symmetry: Symmetry::Both,
+            floor_percent: 0.25,
+        })
+    }
+
+    /// broad random walkers mirrored top to bottom
+    pub fn reflected_halls() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings {
+            algorithm: DlaAlgorithm::WalkInwards,
+            brush_size: 2,
+            symmetry: Symmetry::Vertical,
+            floor_percent: 0.25,
+        })
+    }
+
+    /// random walkers mirrored left to right only
+    pub fn rorschach() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings {
+            algorithm: DlaAlgorithm::WalkInwards,
+            brush_size: 1,
+            symmetry: Symmetry::Horizontal,
+            floor_percent: 0.25,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use super::{BuilderMap, InitialMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// where each digger after the first one sets off from
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode {
+    StartingPoint,
+    Random,
+}
+
+#[derive(Copy, Clone)]
+pub struct DrunkardSettings {
+    pub spawn_mode: DrunkSpawnMode,
+    // steps a digger takes before it gives up
+    pub lifetime: i32,
+    // stop sending diggers once this much of the map is floor
+    pub floor_percent: f32,
+}
+
+/// diggers stumbling about from the centre, carving as they go,
+/// until enough of the map is open
+pub struct DrunkardsWalkBuilder {
+    settings: DrunkardSettings,
+}
+
+impl InitialMapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let start = (map.width / 2, map.height / 2);
+        let start_idx = map.xy_idx(start.0, start.1);
+        map.tiles[start_idx] = TileType::Floor;
+
+        let total_tiles = (map.width * map.height) as usize;
+        let desired_floor = (self.settings.floor_percent * total_tiles as f32) as usize;
+        let mut floor_count = 1;
+        let mut digger_count = 0;
+
+        // the settings are tuned for an 80x43 map, bigger maps need diggers
+        // that walk further or the middle is all they ever reach
+        let scale = (total_tiles as f32 / (80.0 * 43.0)).max(1.0);
+        let lifetime = (self.settings.lifetime as f32 * scale) as i32;
+        // a digger that only retreads old floor adds nothing, stop eventually either way
+        let max_diggers = total_tiles / 4;
+
+        while floor_count < desired_floor && digger_count < max_diggers {
+            let (mut x, mut y) = if digger_count == 0 || self.settings.spawn_mode == DrunkSpawnMode::StartingPoint {
+                start
+            } else {
+                // somewhere already dug, so every new tunnel connects to the rest
+                let floors = map
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| **tile == TileType::Floor)
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<_>>();
+                map.idx_xy(floors[rng.random_slice_index(&floors).unwrap()])
+            };
+
+            for _ in 0..lifetime {
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] == TileType::Wall {
+                    map.tiles[idx] = TileType::Floor;
+                    floor_count += 1;
+                }
+
+                match rng.roll_dice(1, 4) {
+                    1 if x > 2 => x -= 1,
+                    2 if x < map.width - 2 => x += 1,
+                    3 if y > 2 => y -= 1,
+                    4 if y < map.height - 2 => y += 1,
+                    _ => {}
+                }
+            }
+
+            digger_count += 1;
+            map.take_snapshot();
+        }
+    }
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(settings: DrunkardSettings) -> Box<DrunkardsWalkBuilder> {
+        Box::new(DrunkardsWalkBuilder { settings })
+    }
+
+    /// every digger starts in the middle, one big open blob
+    pub fn open_area() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::StartingPoint,
+            lifetime: 400,
+            floor_percent: 0.5,
+        })
+    }
+
+    /// diggers start all over the dug area, wide halls branching off
+    pub fn open_halls() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            lifetime: 400,
+            floor_percent: 0.5,
+        })
+    }
+
+    /// short lived diggers and less floor, narrow twisting passages
+    pub fn winding_passages() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            lifetime: 100,
+            floor_percent: 0.4,
+        })
+    }
+}
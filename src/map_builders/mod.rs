@@ -0,0 +1,300 @@
+use super::{Map, Rect, TileType};
+use rltk::RandomNumberGenerator;
+
+mod simple_map;
+use simple_map::SimpleMapBuilder;
+
+mod bsp_dungeon;
+use bsp_dungeon::BspDungeonBuilder;
+
+mod cellular_automata;
+use cellular_automata::CellularAutomataBuilder;
+
+mod drunkard;
+use drunkard::DrunkardsWalkBuilder;
+
+mod dla;
+use dla::DlaBuilder;
+
+mod common;
+
+mod prefab;
+use prefab::{PrefabLevel, PrefabVaults, HAND_MADE_LEVELS};
+
+mod room_based;
+use room_based::{RoomBasedDoors, RoomBasedSpawnRegions, RoomBasedStairs, RoomBasedStartingPosition};
+
+mod area_starting_position;
+use area_starting_position::CentreStartingPosition;
+
+mod cull_unreachable;
+use cull_unreachable::CullUnreachable;
+
+mod distant_exit;
+use distant_exit::DistantExit;
+
+mod region_spawner;
+use region_spawner::{GridSpawnRegions, RegionSpawner};
+
+mod validation;
+use validation::validate;
+
+#[cfg(test)]
+mod tests;
+
+/// everything the stages of one build share and hand on to the next stage
+pub struct BuilderMap {
+    pub map: Map,
+    pub starting_position: Option<(i32, i32)>,
+    // groups of tiles the spawner fills, rooms or patches of cave
+    pub spawn_regions: Vec<Vec<usize>>,
+    pub spawn_list: Vec<(usize, String)>,
+}
+
+impl BuilderMap {
+    fn new(depth: i32) -> BuilderMap {
+        let (width, height) = level_size(depth);
+        BuilderMap {
+            map: Map::new(depth, width, height),
+            starting_position: None,
+            spawn_regions: Vec::new(),
+            spawn_list: Vec::new(),
+        }
+    }
+
+    /// the map records its own steps, this marks the end of a stage
+    pub fn take_snapshot(&mut self) {
+        self.map.take_snapshot();
+    }
+}
+
+/// how many times a level is built from scratch before its generator gives up
+pub const MAX_ATTEMPTS: usize = 10;
+
+/// what the game needs from a finished map generator
+pub trait MapBuilder {
+    /// one try at a level, the error is why it failed validation
+    fn build_attempt(&mut self, rng: &mut RandomNumberGenerator) -> Result<(), String>;
+
+    /// a level that fails validation is thrown away and built again from scratch,
+    /// the error is why the last attempt failed
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) -> Result<(), String> {
+        let mut result = Ok(());
+        for _attempt in 0..MAX_ATTEMPTS {
+            result = self.build_attempt(rng);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> (i32, i32);
+    fn get_spawn_list(&self) -> &[(usize, String)];
+    fn get_snapshot_history(&self) -> Vec<Map>;
+    /// keep every step of generation for the visualiser, off unless asked for
+    fn record_history(&mut self);
+}
+
+/// a generator that lays out a level from solid rock
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// a stage that works on whatever the stages before it produced
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// levels grow with depth, from a bit over one screen to 200x120
+pub fn level_size(depth: i32) -> (i32, i32) {
+    let level = depth - 1;
+    ((100 + level * 20).min(200), (60 + level * 12).min(120))
+}
+
+/// one initial generator followed by any number of meta stages, run in order
+pub struct BuilderChain {
+    depth: i32,
+    starter: Box<dyn InitialMapBuilder>,
+    builders: Vec<Box<dyn MetaMapBuilder>>,
+    build_data: BuilderMap,
+    record_history: bool,
+}
+
+impl BuilderChain {
+    pub fn new(depth: i32, starter: Box<dyn InitialMapBuilder>) -> BuilderChain {
+        BuilderChain { depth, starter, builders: Vec::new(), build_data: BuilderMap::new(depth), record_history: false }
+    }
+
+    pub fn with(mut self, metabuilder: Box<dyn MetaMapBuilder>) -> BuilderChain {
+        self.builders.push(metabuilder);
+        self
+    }
+}
+
+impl MapBuilder for BuilderChain {
+    fn build_attempt(&mut self, rng: &mut RandomNumberGenerator) -> Result<(), String> {
+        self.build_data = BuilderMap::new(self.depth);
+        self.build_data.map.record_history = self.record_history;
+        self.starter.build_map(rng, &mut self.build_data);
+        self.build_data.take_snapshot();
+
+        for metabuilder in self.builders.iter_mut() {
+            metabuilder.build_map(rng, &mut self.build_data);
+            self.build_data.take_snapshot();
+        }
+
+        validate(&self.build_data)
+    }
+
+    fn get_map(&self) -> Map {
+        let mut map = self.build_data.map.clone();
+        // the history is only for the visualiser, the game map doesn't carry it around
+        map.history.clear();
+        map
+    }
+
+    fn get_starting_position(&self) -> (i32, i32) {
+        self.build_data
+            .starting_position
+            .expect("builder chain finished without a starting position")
+    }
+
+    fn get_spawn_list(&self) -> &[(usize, String)] {
+        &self.build_data.spawn_list
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        let map = &self.build_data.map;
+        map.history
+            .iter()
+            .map(|tiles| Map {
+                tiles: tiles.clone(),
+                revealed_tiles: vec![true; tiles.len()],
+                ..Map::new(map.depth, map.width, map.height)
+            })
+            .collect()
+    }
+
+    fn record_history(&mut self) {
+        self.record_history = true;
+    }
+}
+
+fn simple_map(depth: i32) -> BuilderChain {
+    BuilderChain::new(depth, SimpleMapBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(RoomBasedStairs::new())
+        .with(RoomBasedDoors::new())
+        .with(RoomBasedSpawnRegions::new())
+        .with(RegionSpawner::new())
+        .with(PrefabVaults::new())
+}
+
+fn bsp_dungeon(depth: i32) -> BuilderChain {
+    BuilderChain::new(depth, BspDungeonBuilder::new())
+        .with(RoomBasedStartingPosition::new())
+        .with(RoomBasedStairs::new())
+        .with(RoomBasedDoors::new())
+        .with(RoomBasedSpawnRegions::new())
+        .with(RegionSpawner::new())
+        .with(PrefabVaults::new())
+}
+
+/// generators without rooms start in the middle, lose whatever the start
+/// can't reach, put the stairs far away and spawn in grid squares
+fn open_area(depth: i32, starter: Box<dyn InitialMapBuilder>) -> BuilderChain {
+    BuilderChain::new(depth, starter)
+        .with(CentreStartingPosition::new())
+        .with(CullUnreachable::new())
+        .with(DistantExit::new())
+        .with(GridSpawnRegions::new())
+        .with(RegionSpawner::new())
+        .with(PrefabVaults::new())
+}
+
+/// a hand made level is taken as drawn, nothing is culled or spawned on top
+fn prefab_level(depth: i32, template: &str) -> BuilderChain {
+    BuilderChain::new(depth, PrefabLevel::new(template))
+}
+
+/// makes a fresh builder for the given depth
+type BuilderFn = fn(i32) -> Box<dyn MapBuilder>;
+
+/// every complete generator by name, used for random picks and for `--builder <name>`
+pub const BUILDERS: &[(&str, BuilderFn)] = &[
+    ("simple", |depth| Box::new(simple_map(depth))),
+    ("bsp", |depth| Box::new(bsp_dungeon(depth))),
+    ("cave", |depth| Box::new(open_area(depth, CellularAutomataBuilder::new()))),
+    ("drunkard_open", |depth| Box::new(open_area(depth, DrunkardsWalkBuilder::open_area()))),
+    ("drunkard_halls", |depth| Box::new(open_area(depth, DrunkardsWalkBuilder::open_halls()))),
+    ("drunkard_winding", |depth| Box::new(open_area(depth, DrunkardsWalkBuilder::winding_passages()))),
+    ("dla_walk", |depth| Box::new(open_area(depth, DlaBuilder::walk_inwards()))),
+    ("dla_attractor", |depth| Box::new(open_area(depth, DlaBuilder::central_attractor()))),
+    ("dla_insectoid", |depth| Box::new(open_area(depth, DlaBuilder::insectoid()))),
+    ("dla_rorschach", |depth| Box::new(open_area(depth, DlaBuilder::rorschach()))),
+    ("dla_reflected", |depth| Box::new(open_area(depth, DlaBuilder::reflected_halls()))),
+];
+
+/// a registered generator, or a level file when the name ends in `.txt`
+pub fn builder_by_name(name: &str, depth: i32) -> Option<Box<dyn MapBuilder>> {
+    if name.ends_with(".txt") {
+        return match std::fs::read_to_string(name) {
+            Ok(template) => Some(Box::new(prefab_level(depth, &template))),
+            Err(e) => {
+                rltk::console::log(format!("Unable to read level {}: {}", name, e));
+                None
+            }
+        };
+    }
+
+    BUILDERS
+        .iter()
+        .find(|(builder_name, _)| *builder_name == name)
+        .map(|(_, builder)| builder(depth))
+}
+
+pub fn random_builder(depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    let (_, builder) = rng.random_slice_entry(BUILDERS).unwrap();
+    builder(depth)
+}
+
+/// pick and run a generator for this depth, when it keeps failing validation
+/// the level falls back to plain rooms and corridors, rebuilt until they pass
+/// (they pass first time on every swept seed) so the game always gets a valid
+/// start. `record_history` keeps the generation steps for the visualiser
+pub fn build_level(
+    depth: i32,
+    rng: &mut RandomNumberGenerator,
+    name: Option<&str>,
+    record_history: bool,
+) -> Box<dyn MapBuilder> {
+    let mut builder = level_builder(depth, rng, name);
+    if record_history {
+        builder.record_history();
+    }
+    if let Err(e) = builder.build_map(rng) {
+        rltk::console::log(format!("Map generation gave up ({}), using rooms and corridors", e));
+        builder = Box::new(simple_map(depth));
+        if record_history {
+            builder.record_history();
+        }
+        while let Err(e) = builder.build_map(rng) {
+            rltk::console::log(format!("Rooms and corridors failed validation too ({}), trying again", e));
+        }
+    }
+    builder
+}
+
+/// the named builder when one was asked for and exists, then the hand made
+/// level for this depth if there is one, otherwise a random generator
+fn level_builder(depth: i32, rng: &mut RandomNumberGenerator, name: Option<&str>) -> Box<dyn MapBuilder> {
+    if let Some(builder) = name.and_then(|name| builder_by_name(name, depth)) {
+        return builder;
+    }
+    match HAND_MADE_LEVELS.iter().find(|(level_depth, _)| *level_depth == depth) {
+        Some((_, template)) => Box::new(prefab_level(depth, template)),
+        None => random_builder(depth, rng),
+    }
+}
@@ -0,0 +1,236 @@
+use super::common::distances_from_start;
+use super::{BuilderMap, InitialMapBuilder, Map, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// a hand drawn piece of map, one char per tile:
+///
+/// `#` wall, `.` or space floor, `>` stairs down, `@` where the player starts,
+/// and spawns on floor: `+` door, `g` goblin, `o` orc, `O` ogre,
+/// `!` health potion, `?` magic missile scroll, `/` dagger, `[` leather armor.
+/// anything else is floor
+pub struct Prefab {
+    pub width: i32,
+    pub height: i32,
+    cells: Vec<char>,
+}
+
+impl Prefab {
+    /// short lines are padded with floor, so editors stripping trailing spaces don't matter
+    pub fn parse(template: &str) -> Prefab {
+        let lines = template.lines().map(|line| line.trim_end_matches('\r')).collect::<Vec<_>>();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for line in lines.iter() {
+            let mut row = line.chars().collect::<Vec<_>>();
+            row.resize(width, '.');
+            cells.extend(row);
+        }
+
+        Prefab { width: width as i32, height: lines.len() as i32, cells }
+    }
+
+    fn cell(&self, x: i32, y: i32) -> char {
+        self.cells[(y * self.width + x) as usize]
+    }
+}
+
+fn legend_spawn(cell: char) -> Option<&'static str> {
+    match cell {
+        'g' => Some("Goblin"),
+        'o' => Some("Orc"),
+        'O' => Some("Ogre"),
+        '!' => Some("Health Potion"),
+        '?' => Some("Magic Missile Scroll"),
+        '/' => Some("Dagger"),
+        '[' => Some("Leather Armor"),
+        '+' => Some("Door"),
+        _ => None,
+    }
+}
+
+/// copy the prefab onto the map with its top left corner at x, y,
+/// the outermost ring of the map always stays wall
+fn stamp(build_data: &mut BuilderMap, prefab: &Prefab, x: i32, y: i32) {
+    let map = &mut build_data.map;
+    for prefab_y in 0..prefab.height {
+        for prefab_x in 0..prefab.width {
+            let (map_x, map_y) = (x + prefab_x, y + prefab_y);
+            if map_x < 1 || map_x >= map.width - 1 || map_y < 1 || map_y >= map.height - 1 {
+                continue;
+            }
+            let idx = map.xy_idx(map_x, map_y);
+            let cell = prefab.cell(prefab_x, prefab_y);
+            map.tiles[idx] = match cell {
+                '#' => TileType::Wall,
+                '>' => TileType::DownStairs,
+                _ => TileType::Floor,
+            };
+            if cell == '@' {
+                build_data.starting_position = Some((map_x, map_y));
+            }
+            if let Some(name) = legend_spawn(cell) {
+                build_data.spawn_list.push((idx, name.to_string()));
+            }
+        }
+    }
+    map.take_snapshot();
+}
+
+/// a whole level drawn by hand, for tutorials and boss floors
+pub struct PrefabLevel {
+    template: String,
+}
+
+impl InitialMapBuilder for PrefabLevel {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        // the level is exactly as big as the drawing
+        let prefab = Prefab::parse(&self.template);
+        build_data.map = Map::new(build_data.map.depth, prefab.width, prefab.height);
+        stamp(build_data, &prefab, 0, 0);
+
+        // a level without an `@` starts on its first floor tile
+        if build_data.starting_position.is_none() {
+            let map = &build_data.map;
+            if let Some(idx) = map.tiles.iter().position(|tile| *tile == TileType::Floor) {
+                build_data.starting_position = Some(map.idx_xy(idx));
+            }
+        }
+    }
+}
+
+impl PrefabLevel {
+    pub fn new(template: &str) -> Box<PrefabLevel> {
+        Box::new(PrefabLevel { template: template.to_string() })
+    }
+}
+
+/// the hand made levels that replace generation at their depth
+pub const HAND_MADE_LEVELS: &[(i32, &str)] = &[(6, include_str!("../../raws/levels/ogre_den.txt"))];
+
+/// a vault template and the depths it can turn up at
+pub struct Vault {
+    pub template: &'static str,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
+
+pub const VAULTS: &[Vault] = &[
+    Vault { template: include_str!("../../raws/vaults/treasure_closet.txt"), min_depth: 1, max_depth: 100 },
+    Vault { template: include_str!("../../raws/vaults/goblin_camp.txt"), min_depth: 1, max_depth: 4 },
+    Vault { template: include_str!("../../raws/vaults/orc_barracks.txt"), min_depth: 3, max_depth: 8 },
+];
+
+/// stamp a few vaults into open floor of a finished map, a spot is only
+/// used when everything that was reachable before still is afterwards
+pub struct PrefabVaults {}
+
+impl MetaMapBuilder for PrefabVaults {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        const MAX_ATTEMPTS: usize = 20;
+
+        let depth = build_data.map.depth;
+        let eligible = VAULTS
+            .iter()
+            .filter(|vault| depth >= vault.min_depth && depth <= vault.max_depth)
+            .collect::<Vec<_>>();
+        if eligible.is_empty() {
+            return;
+        }
+
+        let num_vaults = rng.roll_dice(1, 3) - 1;
+        let mut used: Vec<(i32, i32, i32, i32)> = Vec::new();
+        for _ in 0..num_vaults {
+            let prefab = Prefab::parse(eligible[rng.random_slice_index(&eligible).unwrap()].template);
+            let mut spots = fitting_spots(build_data, &prefab, &used);
+
+            for _attempt in 0..MAX_ATTEMPTS {
+                if spots.is_empty() {
+                    break;
+                }
+                let (x, y) = spots.swap_remove(rng.random_slice_index(&spots).unwrap());
+                if place_if_connected(build_data, &prefab, x, y) {
+                    used.push((x, y, prefab.width, prefab.height));
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl PrefabVaults {
+    pub fn new() -> Box<PrefabVaults> {
+        Box::new(PrefabVaults {})
+    }
+}
+
+/// every top left corner where the vault lies wholly on plain floor,
+/// clear of the start and of vaults already placed
+fn fitting_spots(build_data: &BuilderMap, prefab: &Prefab, used: &[(i32, i32, i32, i32)]) -> Vec<(i32, i32)> {
+    let map = &build_data.map;
+    let mut spots = Vec::new();
+    for y in 1..map.height - prefab.height {
+        for x in 1..map.width - prefab.width {
+            let overlaps_vault = used
+                .iter()
+                .any(|(ux, uy, uw, uh)| x < ux + uw && x + prefab.width > *ux && y < uy + uh && y + prefab.height > *uy);
+            if overlaps_vault {
+                continue;
+            }
+
+            let covers_start = build_data.starting_position.is_some_and(|(sx, sy)| {
+                sx >= x && sx < x + prefab.width && sy >= y && sy < y + prefab.height
+            });
+            let all_floor = (y..y + prefab.height)
+                .all(|fy| (x..x + prefab.width).all(|fx| map.tiles[map.xy_idx(fx, fy)] == TileType::Floor));
+            if all_floor && !covers_start {
+                spots.push((x, y));
+            }
+        }
+    }
+    spots
+}
+
+/// stamp the vault, keep it only if no floor was cut off from the start
+fn place_if_connected(build_data: &mut BuilderMap, prefab: &Prefab, x: i32, y: i32) -> bool {
+    let Some((start_x, start_y)) = build_data.starting_position else {
+        return false;
+    };
+
+    let saved_tiles = build_data.map.tiles.clone();
+    let saved_spawns = build_data.spawn_list.len();
+    let saved_history = build_data.map.history.len();
+
+    // whatever the spawner put under the vault makes way for the vault's own spawns
+    let footprint = |idx: usize, map: &Map| {
+        let (sx, sy) = map.idx_xy(idx);
+        sx >= x && sx < x + prefab.width && sy >= y && sy < y + prefab.height
+    };
+    let displaced = {
+        let map = &build_data.map;
+        let (inside, outside): (Vec<_>, Vec<_>) =
+            build_data.spawn_list.drain(..).partition(|(idx, _)| footprint(*idx, map));
+        build_data.spawn_list = outside;
+        inside
+    };
+
+    stamp(build_data, prefab, x, y);
+
+    let map = &mut build_data.map;
+    let start_idx = map.xy_idx(start_x, start_y);
+    let dijkstra = distances_from_start(map, start_idx);
+    let connected = map
+        .tiles
+        .iter()
+        .enumerate()
+        .all(|(idx, tile)| *tile == TileType::Wall || dijkstra.map[idx] != f32::MAX);
+
+    if !connected {
+        map.tiles = saved_tiles;
+        map.history.truncate(saved_history);
+        map.populates_blocked();
+        build_data.spawn_list.truncate(saved_spawns - displaced.len());
+        build_data.spawn_list.extend(displaced);
+    }
+    connected
+}
@@ -0,0 +1,83 @@
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use crate::raws;
+use rltk::RandomNumberGenerator;
+
+/// no rooms to go by, chop the map into squares and spawn per square,
+/// except the one the player starts in
+pub struct GridSpawnRegions {}
+
+impl MetaMapBuilder for GridSpawnRegions {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        const REGION_SIZE: i32 = 12;
+
+        let map = &build_data.map;
+        let start_idx = build_data.starting_position.map(|(x, y)| map.xy_idx(x, y));
+        let mut spawn_regions = Vec::new();
+        for region_y in (0..map.height).step_by(REGION_SIZE as usize) {
+            for region_x in (0..map.width).step_by(REGION_SIZE as usize) {
+                let area = (region_y..(region_y + REGION_SIZE).min(map.height))
+                    .flat_map(|y| (region_x..(region_x + REGION_SIZE).min(map.width)).map(move |x| (x, y)))
+                    .map(|(x, y)| map.xy_idx(x, y))
+                    .filter(|idx| map.tiles[*idx] == TileType::Floor)
+                    .collect::<Vec<_>>();
+                if !area.is_empty() && start_idx.is_none_or(|start| !area.contains(&start)) {
+                    spawn_regions.push(area);
+                }
+            }
+        }
+        build_data.spawn_regions = spawn_regions;
+    }
+}
+
+impl GridSpawnRegions {
+    pub fn new() -> Box<GridSpawnRegions> {
+        Box::new(GridSpawnRegions {})
+    }
+}
+
+const MAX_SPAWNS: i32 = 4;
+
+/// a random number of spawns per region, more the deeper it is,
+/// each on its own free floor tile and rolled from the depth's spawn table
+pub struct RegionSpawner {}
+
+impl MetaMapBuilder for RegionSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let table = raws::raws().spawn_table(map.depth);
+
+        for area in build_data.spawn_regions.iter() {
+            let num_spawns = rng.roll_dice(1, MAX_SPAWNS + 3) + (map.depth - 1) - 3;
+
+            let free_tiles = area
+                .iter()
+                .copied()
+                .filter(|idx| map.tiles[*idx] == TileType::Floor)
+                .collect::<Vec<_>>();
+            if free_tiles.is_empty() {
+                continue;
+            }
+
+            let mut spawn_points: Vec<(usize, String)> = Vec::new();
+            for _ in 0..num_spawns {
+                // give up on a crowded region rather than stacking spawns
+                for _attempt in 0..20 {
+                    let idx = free_tiles[rng.random_slice_index(&free_tiles).unwrap()];
+                    if spawn_points.iter().all(|(taken, _)| *taken != idx) {
+                        if let Some(name) = table.roll(rng) {
+                            spawn_points.push((idx, name));
+                        }
+                        break;
+                    }
+                }
+            }
+            build_data.spawn_list.append(&mut spawn_points);
+        }
+    }
+}
+
+impl RegionSpawner {
+    pub fn new() -> Box<RegionSpawner> {
+        Box::new(RegionSpawner {})
+    }
+}
@@ -0,0 +1,108 @@
+use super::{BuilderMap, MetaMapBuilder, TileType};
+use rltk::RandomNumberGenerator;
+
+/// start in the middle of the first room built
+pub struct RoomBasedStartingPosition {}
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let room = build_data.map.rooms.first().expect("room based start needs rooms");
+        build_data.starting_position = Some(room.center());
+    }
+}
+
+impl RoomBasedStartingPosition {
+    pub fn new() -> Box<RoomBasedStartingPosition> {
+        Box::new(RoomBasedStartingPosition {})
+    }
+}
+
+/// the way down sits in the last room built, the far end of the layout
+pub struct RoomBasedStairs {}
+
+impl MetaMapBuilder for RoomBasedStairs {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let (stairs_x, stairs_y) = map.rooms.last().expect("room based stairs need rooms").center();
+        let stairs_idx = map.xy_idx(stairs_x, stairs_y);
+        map.tiles[stairs_idx] = TileType::DownStairs;
+    }
+}
+
+impl RoomBasedStairs {
+    pub fn new() -> Box<RoomBasedStairs> {
+        Box::new(RoomBasedStairs {})
+    }
+}
+
+/// every room but the first is a spawn region, the player gets a quiet start
+pub struct RoomBasedSpawnRegions {}
+
+impl MetaMapBuilder for RoomBasedSpawnRegions {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        build_data.spawn_regions = map
+            .rooms
+            .iter()
+            .skip(1)
+            .map(|room| {
+                (room.y1 + 1..=room.y2)
+                    .flat_map(|y| (room.x1 + 1..=room.x2).map(move |x| (x, y)))
+                    .map(|(x, y)| map.xy_idx(x, y))
+                    .collect()
+            })
+            .collect();
+    }
+}
+
+impl RoomBasedSpawnRegions {
+    pub fn new() -> Box<RoomBasedSpawnRegions> {
+        Box::new(RoomBasedSpawnRegions {})
+    }
+}
+
+/// a door wherever a corridor pokes through a room's wall, that is a floor
+/// tile in the wall ring with wall on both sides of it
+pub struct RoomBasedDoors {}
+
+impl MetaMapBuilder for RoomBasedDoors {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let is_wall = |x: i32, y: i32| map.tiles[map.xy_idx(x, y)] == TileType::Wall;
+
+        let mut doors: Vec<usize> = Vec::new();
+        for room in map.rooms.iter() {
+            // rooms carve x1 + 1 to x2, so the walls are at x1 and x2 + 1
+            let (left, right, top, bottom) = (room.x1, room.x2 + 1, room.y1, room.y2 + 1);
+            let horizontal = (left + 1..right).flat_map(|x| [(x, top), (x, bottom)]);
+            let vertical = (top + 1..bottom).flat_map(|y| [(left, y), (right, y)]);
+
+            for (x, y) in horizontal.chain(vertical) {
+                if x < 1 || x >= map.width - 1 || y < 1 || y >= map.height - 1 {
+                    continue;
+                }
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] != TileType::Floor {
+                    continue;
+                }
+
+                let in_gap = (is_wall(x - 1, y) && is_wall(x + 1, y)) || (is_wall(x, y - 1) && is_wall(x, y + 1));
+                let next_to_door = doors.iter().any(|door| {
+                    let (door_x, door_y) = map.idx_xy(*door);
+                    (door_x - x).abs() <= 1 && (door_y - y).abs() <= 1
+                });
+                if in_gap && !next_to_door && build_data.starting_position != Some((x, y)) {
+                    doors.push(idx);
+                }
+            }
+        }
+
+        build_data.spawn_list.extend(doors.into_iter().map(|idx| (idx, "Door".to_string())));
+    }
+}
+
+impl RoomBasedDoors {
+    pub fn new() -> Box<RoomBasedDoors> {
+        Box::new(RoomBasedDoors {})
+    }
+}
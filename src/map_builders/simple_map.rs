@@ -0,0 +1,40 @@
+use super::{BuilderMap, InitialMapBuilder, Rect};
+use rltk::RandomNumberGenerator;
+
+/// rooms dropped at random wherever they fit, each joined to the one before
+pub struct SimpleMapBuilder {}
+
+impl InitialMapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        // 30 tries on an 80x43 map, as many per tile on bigger ones
+        const ROOMS_PER_TILE: f32 = 30.0 / (80.0 * 43.0);
+        const MIN_SIZE: i32 = 6;
+        const MAX_SIZE: i32 = 10;
+
+        let map = &mut build_data.map;
+        let max_rooms = (ROOMS_PER_TILE * (map.width * map.height) as f32) as i32;
+        for _ in 0..max_rooms {
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+            // x max is width - 1 - 1 - w
+            let x = rng.roll_dice(1, map.width - 1 - w) - 1;
+            let y = rng.roll_dice(1, map.height - 1 - h) - 1;
+            let new_room = Rect::new(x, y, w, h);
+            if !map.rooms.iter().any(|other| new_room.intersect(other)) {
+                map.apply_room_to_map(&new_room);
+                if !map.rooms.is_empty() {
+                    let (x1, y1) = new_room.center();
+                    let (x2, y2) = map.rooms.last().unwrap().center();
+                    map.apply_l_tunnel(rng, (x1, y1), (x2, y2));
+                }
+                map.rooms.push(new_room);
+            }
+        }
+    }
+}
+
+impl SimpleMapBuilder {
+    pub fn new() -> Box<SimpleMapBuilder> {
+        Box::new(SimpleMapBuilder {})
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+use crate::raws;
+
+/// seeds per generator in a normal test run, a couple for every depth
+const QUICK_SEEDS: u64 = 16;
+/// seeds per generator in the full sweep, `cargo test --release -- --ignored`
+const FULL_SEEDS: u64 = 1000;
+/// seeds are spread over the depths so every map size is covered
+const DEPTHS: u64 = 8;
+
+/// build `seeds` levels with one generator, every seed must pass validation on
+/// its first attempt so build_map's retries can't hide a generator gone bad
+fn sweep(name: &str, seeds: u64) {
+    raws::load_raws().expect("unable to load raws");
+    let mut failures = Vec::new();
+    for seed in 0..seeds {
+        let depth = 1 + (seed % DEPTHS) as i32;
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        let mut builder = builder_by_name(name, depth).expect("unknown builder");
+        if let Err(e) = builder.build_attempt(&mut rng) {
+            failures.push(format!("seed {} at depth {}: {}", seed, depth, e));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} failed {} of {} first attempts:\n{}",
+        name,
+        failures.len(),
+        seeds,
+        failures.join("\n")
+    );
+}
+
+macro_rules! sweep_tests {
+    ($($test:ident => $name:expr),* $(,)?) => {
+        $(
+        #[test]
+        fn $test() {
+            sweep($name, QUICK_SEEDS);
+        }
+        )*
+
+        #[test]
+        #[ignore = "builds thousands of levels, run with --ignored in release"]
+        fn full_seed_sweep() {
+            for name in [$($name),*] {
+                sweep(name, FULL_SEEDS);
+            }
+        }
+
+        #[test]
+        fn every_builder_is_swept() {
+            let swept = [$($name),*];
+            for (name, _) in BUILDERS {
+                assert!(swept.contains(name), "{} has no seed sweep", name);
+            }
+        }
+    };
+}
+
+sweep_tests! {
+    simple_sweep => "simple",
+    bsp_sweep => "bsp",
+    cave_sweep => "cave",
+    drunkard_open_sweep => "drunkard_open",
+    drunkard_halls_sweep => "drunkard_halls",
+    drunkard_winding_sweep => "drunkard_winding",
+    dla_walk_sweep => "dla_walk",
+    dla_attractor_sweep => "dla_attractor",
+    dla_insectoid_sweep => "dla_insectoid",
+    dla_rorschach_sweep => "dla_rorschach",
+    dla_reflected_sweep => "dla_reflected",
+}
+
+#[test]
+fn hand_made_levels_are_valid() {
+    raws::load_raws().expect("unable to load raws");
+    for (depth, template) in HAND_MADE_LEVELS {
+        let mut rng = RandomNumberGenerator::seeded(0);
+        let mut builder = prefab_level(*depth, template);
+        builder.build_map(&mut rng).expect("hand made level failed validation");
+    }
+}
+
+/// a small valid level to break in the tests below: a room with the start and the stairs
+fn valid_level() -> BuilderMap {
+    let mut build_data = BuilderMap::new(1);
+    build_data.map.apply_room_to_map(&Rect::new(2, 2, 6, 6));
+    let stairs = build_data.map.xy_idx(7, 7);
+    build_data.map.tiles[stairs] = TileType::DownStairs;
+    build_data.starting_position = Some((3, 3));
+    build_data
+}
+
+#[test]
+fn validation_accepts_a_valid_level() {
+    assert!(validate(&valid_level()).is_ok());
+}
+
+#[test]
+fn validation_rejects_an_open_edge() {
+    let mut build_data = valid_level();
+    build_data.map.tiles[0] = TileType::Floor;
+    assert!(validate(&build_data).is_err());
+}
+
+#[test]
+fn validation_rejects_unreachable_floor() {
+    let mut build_data = valid_level();
+    build_data.map.apply_room_to_map(&Rect::new(20, 20, 4, 4));
+    assert!(validate(&build_data).is_err());
+}
+
+#[test]
+fn validation_rejects_spawns_off_the_floor() {
+    let mut build_data = valid_level();
+    build_data.spawn_list.push((0, "Goblin".to_string()));
+    assert!(validate(&build_data).is_err());
+}
+
+#[test]
+fn validation_rejects_a_start_in_the_wall() {
+    let mut build_data = valid_level();
+    build_data.starting_position = Some((1, 1));
+    assert!(validate(&build_data).is_err());
+}
+
+#[test]
+fn tunnels_never_open_the_edge() {
+    let mut build_data = BuilderMap::new(1);
+    let map = &mut build_data.map;
+    let (width, height) = (map.width, map.height);
+    // the old bounds check skipped only tile 0 and carved the rest of the top row
+    map.apply_horizontal_tunnel(0, width - 1, 0);
+    map.apply_vertical_tunnel(0, height - 1, width - 1);
+    map.apply_horizontal_tunnel(0, width - 1, 1);
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        let (x, y) = map.idx_xy(idx);
+        let on_edge = x == 0 || x == width - 1 || y == 0 || y == height - 1;
+        assert!(!on_edge || *tile == TileType::Wall, "edge carved at {},{}", x, y);
+    }
+    assert!(map.tiles[map.xy_idx(1, 1)] == TileType::Floor);
+    assert!(map.tiles[map.xy_idx(width - 2, 1)] == TileType::Floor);
+}
+
+#[test]
+fn history_is_only_kept_for_the_visualiser() {
+    raws::load_raws().expect("unable to load raws");
+    let mut rng = RandomNumberGenerator::seeded(1);
+    assert!(build_level(2, &mut rng, Some("drunkard_open"), false).get_snapshot_history().is_empty());
+    let mut rng = RandomNumberGenerator::seeded(1);
+    assert!(!build_level(2, &mut rng, Some("drunkard_open"), true).get_snapshot_history().is_empty());
+}
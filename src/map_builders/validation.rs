@@ -0,0 +1,61 @@
+use super::{BuilderMap, TileType};
+use std::collections::VecDeque;
+
+/// what every finished level has to satisfy, whichever generator made it:
+/// a start on floor, solid wall all the way round the edge, every open tile
+/// reachable from the start, a way down and every spawn standing on floor
+pub fn validate(build_data: &BuilderMap) -> Result<(), String> {
+    let map = &build_data.map;
+
+    let (start_x, start_y) = build_data.starting_position.ok_or("no starting position")?;
+    if start_x < 0 || start_x >= map.width || start_y < 0 || start_y >= map.height {
+        return Err(format!("starting position {},{} is off the map", start_x, start_y));
+    }
+    let start_idx = map.xy_idx(start_x, start_y);
+    if map.tiles[start_idx] != TileType::Floor {
+        return Err(format!("starting position {},{} is not floor", start_x, start_y));
+    }
+
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        let (x, y) = map.idx_xy(idx);
+        let on_edge = x == 0 || x == map.width - 1 || y == 0 || y == map.height - 1;
+        if on_edge && *tile != TileType::Wall {
+            return Err(format!("the edge is open at {},{}", x, y));
+        }
+    }
+
+    // flood out from the start the way movement works, diagonals included
+    let mut reached = vec![false; map.tiles.len()];
+    let mut open = VecDeque::from([start_idx]);
+    reached[start_idx] = true;
+    while let Some(idx) = open.pop_front() {
+        let (x, y) = map.idx_xy(idx);
+        for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+            let (next_x, next_y) = (x + dx, y + dy);
+            if next_x < 0 || next_x >= map.width || next_y < 0 || next_y >= map.height {
+                continue;
+            }
+            let next = map.xy_idx(next_x, next_y);
+            if !reached[next] && map.tiles[next] != TileType::Wall {
+                reached[next] = true;
+                open.push_back(next);
+            }
+        }
+    }
+    if let Some(idx) = (0..map.tiles.len()).find(|idx| map.tiles[*idx] != TileType::Wall && !reached[*idx]) {
+        let (x, y) = map.idx_xy(idx);
+        return Err(format!("{},{} can't be reached from the start", x, y));
+    }
+
+    if !map.tiles.contains(&TileType::DownStairs) {
+        return Err("there is no way down".to_string());
+    }
+
+    for (idx, name) in build_data.spawn_list.iter() {
+        if *idx >= map.tiles.len() || map.tiles[*idx] != TileType::Floor {
+            return Err(format!("{} spawns off the floor at tile {}", name, idx));
+        }
+    }
+
+    Ok(())
+}
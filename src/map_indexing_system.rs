@@ -1,4 +1,4 @@
-use super::{BlocksTile, Map, Position};
+use super::{BlocksTile, BlocksVisibility, Door, Map, Position};
 use specs::prelude::*;
 
 pub struct MapIndexingSystem {}
@@ -12,14 +12,18 @@ impl<'a> System<'a> for MapIndexingSystem {
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, BlocksVisibility>,
+        ReadStorage<'a, Door>,
         Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, poses, blkers, ents) = data;
+        let (mut map, poses, blkers, view_blockers, doors, ents) = data;
 
         map.populates_blocked();
         map.clear_all_content();
+        map.view_blocked.clear();
+        map.closed_doors.clear();
 
         for (ent, position) in (&ents, &poses).join() {
             let idx = map.xy_idx(position.x, position.y);
@@ -29,6 +33,13 @@ impl<'a> System<'a> for MapIndexingSystem {
                 map.blocked[idx] = true;
             }
 
+            if view_blockers.get(ent).is_some() {
+                map.view_blocked.insert(idx);
+            }
+            if doors.get(ent).is_some_and(|door| !door.open) {
+                map.closed_doors.insert(idx);
+            }
+
             map.tile_content[idx].push(ent);
         }
     }
@@ -1,5 +1,9 @@
-use super::{CombatStats, Name, SufferDamage, WantsToMelee};
-use rltk::console;
+use super::attack::{self, AttackRoll};
+use super::{
+    CombatStats, DefenseBonus, Equipped, GameLog, GameStats, LogEntry, MeleePowerBonus, MeleeWeapon, Name,
+    NaturalAttack, SufferDamage, WantsToMelee,
+};
+use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 pub struct MeleeCombatSystem {}
@@ -11,32 +15,94 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameStats>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, NaturalAttack>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut all_wants_melees, names, all_combat_stats, mut damages) = data;
+        let (
+            entities,
+            mut all_wants_melees,
+            names,
+            all_combat_stats,
+            mut damages,
+            player_entity,
+            mut stats,
+            mut log,
+            mut rng,
+            equipped,
+            power_bonuses,
+            defense_bonuses,
+            weapons,
+            natural_attacks,
+        ) = data;
 
-        for (_ent, one_wants_melee, name, one_combat_stats) in
+        for (ent, one_wants_melee, name, one_combat_stats) in
             (&entities, &all_wants_melees, &names, &all_combat_stats).join()
         {
             if one_combat_stats.hp > 0 {
                 let target_combat_stats = all_combat_stats.get(one_wants_melee.target).unwrap();
                 if target_combat_stats.hp > 0 {
                     let target_name = names.get(one_wants_melee.target).unwrap();
-                    let real_dmg =
-                        std::cmp::max(0, one_combat_stats.power - target_combat_stats.defense);
-
-                    if real_dmg == 0 {
-                        console::log(format!(
-                            "{} is unable to hurt {}",
-                            name.name, target_name.name
-                        ));
+
+                    // worn and wielded items add on top of the raw stats
+                    let power_bonus: i32 = (&equipped, &power_bonuses)
+                        .join()
+                        .filter(|(worn, _bonus)| worn.owner == ent)
+                        .map(|(_worn, bonus)| bonus.power)
+                        .sum();
+                    let defense_bonus: i32 = (&equipped, &defense_bonuses)
+                        .join()
+                        .filter(|(worn, _bonus)| worn.owner == one_wants_melee.target)
+                        .map(|(_worn, bonus)| bonus.defense)
+                        .sum();
+
+                    // a wielded weapon replaces fists and claws, bare hands do 1d4
+                    let damage = (&equipped, &weapons)
+                        .join()
+                        .find(|(worn, _weapon)| worn.owner == ent)
+                        .map(|(_worn, weapon)| weapon.damage)
+                        .or_else(|| natural_attacks.get(ent).map(|attack| attack.damage))
+                        .unwrap_or_default();
+
+                    let (roll, amount) = attack::swing(
+                        &mut rng,
+                        one_combat_stats.power + power_bonus,
+                        attack::armour_class(target_combat_stats.defense + defense_bonus),
+                        damage,
+                    );
+
+                    let verb = match roll {
+                        AttackRoll::Fumble => "fumbles an attack on",
+                        AttackRoll::Miss => "misses",
+                        AttackRoll::Hit => "hits",
+                        AttackRoll::Critical => "critically hits",
+                    };
+                    if amount == 0 {
+                        log.push(
+                            LogEntry::new(&name.name, verb)
+                                .target(&target_name.name)
+                                .color(RGB::named(rltk::GREY)),
+                        );
                     } else {
-                        console::log(format!(
-                            "{} hits {}, for {} hp, {} hp left: {}",
-                            name.name, target_name.name, real_dmg, target_name.name, target_combat_stats.hp
-                        ));
-                        SufferDamage::new_damage(&mut damages, one_wants_melee.target, real_dmg);
+                        let color = attack::hit_color(one_wants_melee.target == *player_entity);
+                        log.push(
+                            LogEntry::new(&name.name, verb)
+                                .target(&target_name.name)
+                                .amount(amount)
+                                .color(color),
+                        );
+                        SufferDamage::new_damage(&mut damages, one_wants_melee.target, amount, Some(ent));
+                        if one_wants_melee.target == *player_entity {
+                            stats.last_hit_by = Some(name.name.clone());
+                        }
                     }
                 }
             }
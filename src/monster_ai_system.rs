@@ -1,5 +1,5 @@
-use super::{Map, Monster, Name, Position, ViewShed, WantsToMelee, RunState};
-use rltk::{console, Point};
+use super::{CombatStats, Door, Map, Monster, Position, ViewShed, WantsToMelee, WantsToOpenDoor, RunState, StatusEffects, StatusKind};
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
 
 pub struct MonsterAI {}
@@ -16,37 +16,66 @@ impl<'a> System<'a> for MonsterAI {
         WriteStorage<'a, ViewShed>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Monster>,
-        ReadStorage<'a, Name>,
         WriteStorage<'a, WantsToMelee>,
+        ReadStorage<'a, Door>,
+        WriteStorage<'a, WantsToOpenDoor>,
+        ReadStorage<'a, StatusEffects>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, CombatStats>,
         // ReadStorage<'a, BlocksTile>
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        // let (mut map, player_pos, player_entity, run_state, entities, mut viewsheds, mut poses, monsters, names, mut wants_to_melee) = data;
-        let (mut map, player_pos, player_entity, run_state, entities,  mut viewsheds, mut poses, monsters, names, mut wants_to_melee) = data;
+        let (mut map, player_pos, player_entity, run_state, entities,  mut viewsheds, mut poses, monsters, mut wants_to_melee, doors, mut wants_to_open, statuses, mut rng, combat_stats) = data;
 
         if *run_state != RunState::MonsterTurn {return}
 
-        for (ent, viewshed, pos, _monster, name) in
-            (&entities, &mut viewsheds, &mut poses, &monsters, &names).join() {
+        for (ent, viewshed, pos, _monster) in
+            (&entities, &mut viewsheds, &mut poses, &monsters).join() {
+            let effects = statuses.get(ent);
+            if effects.is_some_and(|effects| effects.has(StatusKind::Sleep)) {
+                continue
+            }
+            // confused monsters stagger to a random free neighbour and attack nobody,
+            // the player doesn't block their tile so it is checked for separately
+            if effects.is_some_and(|effects| effects.has(StatusKind::Confusion)) {
+                let (x, y) = (pos.x + rng.range(-1, 2), pos.y + rng.range(-1, 2));
+                let step_idx = map.xy_idx(x, y);
+                let occupied = Point::new(x, y) == *player_pos
+                    || map.tile_content[step_idx].iter().any(|other| combat_stats.get(*other).is_some());
+                if !map.blocked[step_idx] && !occupied {
+                    let old_idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[old_idx] = false;
+                    (pos.x, pos.y) = (x, y);
+                    map.blocked[step_idx] = true;
+                    viewshed.dirty = true;
+                }
+                continue
+            }
+
             // player_pos is not a reference type, here is a explicitly deref coercion
             // no deref coercion here, because deref coercion take ref and give another ref
             let distance =
                 rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
             if distance < 1.5 {
-                // console::log(format!("{} attack", name.name));
                 wants_to_melee.insert(ent, WantsToMelee { target: *player_entity }).expect("unable to insert attach");
             }
             // bug here, two monster search path and will get overlap
             // how to avoid that?
             else if viewshed.visible_tiles.contains(&*player_pos) {
                 let start = map.xy_idx(pos.x, pos.y);
-                console::log(format!("{} pos: {},{}", name.name, pos.x, pos.y));
                 let end = map.xy_idx(player_pos.x, player_pos.y);
                 // &*map is a explicitly deref, map is of type FetchMut, what we want is struct Map
                 let path = rltk::a_star_search(start, end, &*map);
                 if path.success && path.steps.len() > 1 {
                     let step_idx = path.steps[1];
+                    // a closed door on the way costs this turn to open
+                    if map.closed_doors.contains(&step_idx) {
+                        if let Some(door) = map.tile_content[step_idx].iter().find(|ent| doors.get(**ent).is_some()) {
+                            wants_to_open.insert(ent, WantsToOpenDoor { door: *door }).expect("unable to insert open door");
+                        }
+                        continue
+                    }
                     if map.blocked[step_idx] {
                         continue
                     }
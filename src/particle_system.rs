@@ -0,0 +1,81 @@
+use super::{spawner::RENDER_ORDER_PARTICLE, ParticleLifetime, Position, Renderable};
+use rltk::{Rltk, RGB};
+use specs::prelude::*;
+
+struct ParticleRequest {
+    x: i32,
+    y: i32,
+    fg: RGB,
+    bg: RGB,
+    glyph: rltk::FontCharType,
+    lifetime_ms: f32,
+}
+
+/// systems queue particles here, they can't create entities with components themselves
+#[derive(Default)]
+pub struct ParticleBuilder {
+    requests: Vec<ParticleRequest>,
+}
+
+impl ParticleBuilder {
+    pub fn request(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, glyph: rltk::FontCharType, lifetime_ms: f32) {
+        self.requests.push(ParticleRequest { x, y, fg, bg, glyph, lifetime_ms });
+    }
+}
+
+/// turns the queued requests into particle entities, particles are never saved
+pub struct ParticleSpawnSystem {}
+
+impl<'a> System<'a> for ParticleSpawnSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, ParticleLifetime>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut builder, mut positions, mut renderables, mut lifetimes) = data;
+
+        for request in builder.requests.drain(..) {
+            let particle = entities.create();
+            positions
+                .insert(particle, Position { x: request.x, y: request.y })
+                .expect("unable to insert particle position");
+            renderables
+                .insert(
+                    particle,
+                    Renderable {
+                        glyph: request.glyph,
+                        fg: request.fg,
+                        bg: request.bg,
+                        render_order: RENDER_ORDER_PARTICLE,
+                    },
+                )
+                .expect("unable to insert particle renderable");
+            lifetimes
+                .insert(particle, ParticleLifetime { lifetime_ms: request.lifetime_ms })
+                .expect("unable to insert particle lifetime");
+        }
+    }
+}
+
+/// age every particle by the last frame and delete the expired ones, runs every
+/// frame rather than every turn so flashes fade while the game waits for input
+pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
+    let mut dead = Vec::new();
+    {
+        let mut lifetimes = ecs.write_storage::<ParticleLifetime>();
+        let entities = ecs.entities();
+        for (ent, particle) in (&entities, &mut lifetimes).join() {
+            particle.lifetime_ms -= ctx.frame_time_ms;
+            if particle.lifetime_ms < 0.0 {
+                dead.push(ent);
+            }
+        }
+    }
+    for ent in dead {
+        ecs.delete_entity(ent).expect("unable to delete particle");
+    }
+}
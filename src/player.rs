@@ -1,12 +1,32 @@
 
-use super::{Point, RunState, ViewShed, CombatStats, Name, WantsToMelee};
-use rltk::{Rltk, VirtualKeyCode, console};
+use super::{Point, RunState, ViewShed, CombatStats, Name, WantsToMelee, GameLog, LogEntry, Item, WantsToPickupItem, Door, WantsToOpenDoor, WantsToCloseDoor, StatusEffects, StatusKind, Equipped, RangedWeapon, Ammunition, InPack, TargetingFor, gui};
+use rltk::{Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
-use super::{Map, Player, Position, State, HEIGHT, WIDTH};
+use super::{Map, Player, Position, State, TileType};
+
+/// whether the player is under the effect right now
+fn player_has(ecs: &World, kind: StatusKind) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    ecs.read_storage::<StatusEffects>().get(*player_entity).is_some_and(|effects| effects.has(kind))
+}
 
 // failed to move when destination is out of range or destination is a wall
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    // confusion picks the direction, whichever key was pressed; never standing
+    // still, the player's own tile would read as someone to attack
+    let (delta_x, delta_y) = if player_has(ecs, StatusKind::Confusion) {
+        let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+        loop {
+            let delta = (rng.range(-1, 2), rng.range(-1, 2));
+            if delta != (0, 0) {
+                break delta;
+            }
+        }
+    } else {
+        (delta_x, delta_y)
+    };
+
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<ViewShed>();
@@ -16,20 +36,30 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let combat_stats = ecs.read_storage::<CombatStats>();
     let names = ecs.read_storage::<Name>();
     let mut all_wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let doors = ecs.read_storage::<Door>();
+    let mut wants_to_open = ecs.write_storage::<WantsToOpenDoor>();
+    let mut log = ecs.write_resource::<GameLog>();
 
     for (entity, viewshed, _player, pos) in (&entities, &mut viewsheds, &mut players, &mut positions).join() {
         if pos.x + delta_x < 1 || pos.x + delta_x > map.width-1 || pos.y + delta_y < 1 || pos.y + delta_y > map.height-1 { return; }
         let dest_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
         for potential_target in map.tile_content[dest_idx].iter() {
+            // bumping a closed door opens it instead of moving
+            if doors.get(*potential_target).is_some_and(|door| !door.open) {
+                log.push(LogEntry::new("You", "open the door"));
+                wants_to_open.insert(entity, WantsToOpenDoor { door: *potential_target }).expect("unable to insert open door");
+                return
+            }
+
             match (combat_stats.get(*potential_target), names.get(*potential_target)) {
                 (Some(_cs), Some(name)) => {
-                    console::log(format!("Player Stab {}", name.name));
+                    log.push(LogEntry::new("You", "stab at").target(&name.name));
                     all_wants_to_melee.insert(entity, WantsToMelee { target: *potential_target }).expect("Add target failed");
                     return
                 },
                 (Some(_cs), None) => {
-                    console::log(format!("Player Stab anonymous with id {}", potential_target.id()));
+                    log.push(LogEntry::new("You", "stab at").target(format!("something #{}", potential_target.id())));
                     all_wants_to_melee.insert(entity, WantsToMelee { target: *potential_target }).expect("Add target failed");
                     return
                 },
@@ -43,8 +73,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
 
         if !map.blocked[dest_idx] {
-            pos.x = (pos.x + delta_x).clamp(0, (WIDTH - 1) as i32);
-            pos.y = (pos.y + delta_y).clamp(0, (HEIGHT - 1) as i32);
+            pos.x = (pos.x + delta_x).clamp(0, map.width - 1);
+            pos.y = (pos.y + delta_y).clamp(0, map.height - 1);
             player_pos.x = pos.x;
             player_pos.y = pos.y;
 
@@ -53,10 +83,110 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
+/// queue a pickup of the item underfoot, false when there is nothing to pick up
+fn get_item(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    let target_item = (&entities, &items, &positions)
+        .join()
+        .find(|(_ent, _item, pos)| pos.x == player_pos.x && pos.y == player_pos.y)
+        .map(|(ent, _item, _pos)| ent);
+
+    match target_item {
+        None => {
+            log.push(LogEntry::new("There", "is nothing here to pick up"));
+            false
+        }
+        Some(item) => {
+            let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+            pickup
+                .insert(*player_entity, WantsToPickupItem { collected_by: *player_entity, item })
+                .expect("unable to insert want to pickup");
+            true
+        }
+    }
+}
+
+/// close an open door next to the player, false when there is none to close
+fn try_close_door(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let doors = ecs.read_storage::<Door>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    let neighbours = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))).filter(|delta| *delta != (0, 0));
+    for (dx, dy) in neighbours {
+        let (x, y) = (player_pos.x + dx, player_pos.y + dy);
+        if x < 0 || x >= map.width || y < 0 || y >= map.height {
+            continue;
+        }
+        let content = &map.tile_content[map.xy_idx(x, y)];
+        // a doorway with someone or something in it can't be shut
+        if content.len() != 1 {
+            continue;
+        }
+        if doors.get(content[0]).is_some_and(|door| door.open) {
+            ecs.write_storage::<WantsToCloseDoor>()
+                .insert(*player_entity, WantsToCloseDoor { door: content[0] })
+                .expect("unable to insert close door");
+            log.push(LogEntry::new("You", "close the door"));
+            return true;
+        }
+    }
+
+    log.push(LogEntry::new("There", "is no open door to close"));
+    false
+}
+
+/// the range of the wielded ranged weapon, None with a message when there is
+/// nothing to shoot with or nothing left to shoot
+fn firing_range(ecs: &World) -> Option<i32> {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let weapons = ecs.read_storage::<RangedWeapon>();
+    let backpack = ecs.read_storage::<InPack>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    let Some(weapon) = (&equipped, &weapons).join().find(|(worn, _weapon)| worn.owner == *player_entity) else {
+        log.push(LogEntry::new("You", "have nothing to shoot with"));
+        return None;
+    };
+    if !(&backpack, &ammunition).join().any(|(pack, ammo)| pack.owner == *player_entity && ammo.rounds > 0) {
+        log.push(LogEntry::new("You", "are out of ammunition"));
+        return None;
+    }
+    Some(weapon.1.range)
+}
+
+fn try_next_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    if map.tiles[player_idx] == TileType::DownStairs {
+        true
+    } else {
+        ecs.write_resource::<GameLog>().push(LogEntry::new("There", "is no way down from here"));
+        false
+    }
+}
+
 /// On mac, Numpad is not working as usual, Numpad is mapping into Key
 /// when not adding Key code, the Numpad won't working
 use VirtualKeyCode::*;
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    // asleep, every key but save and quit just lets the turn go by
+    if ctx.key.is_some_and(|key| key != Escape) && player_has(&gs.ecs, StatusKind::Sleep) {
+        gs.ecs.write_resource::<GameLog>().push(LogEntry::new("You", "are fast asleep"));
+        return RunState::PlayerTurn;
+    }
+
     match ctx.key {
         None => return RunState::AwaitingInput,
         Some(k) => match k {
@@ -71,6 +201,57 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             Key7 | Numpad7 | Y => try_move_player(-1, -1, &mut gs.ecs),
             Key3 | Numpad3 | N => try_move_player(1, 1, &mut gs.ecs),
             Key1 | Numpad1 | B => try_move_player(-1, 1, &mut gs.ecs),
+
+            // stairs
+            Period => {
+                if try_next_level(&mut gs.ecs) {
+                    return RunState::NextLevel;
+                }
+                return RunState::AwaitingInput;
+            }
+
+            // doors open by walking into them
+            C => {
+                if !try_close_door(&mut gs.ecs) {
+                    return RunState::AwaitingInput;
+                }
+            }
+
+            // aim the wielded bow, firing takes the turn once a target is picked
+            F => {
+                return match firing_range(&gs.ecs) {
+                    Some(range) => RunState::ShowTargeting {
+                        range,
+                        target_for: TargetingFor::Shot,
+                        cursor: gui::initial_cursor(&gs.ecs, range, TargetingFor::Shot),
+                    },
+                    None => RunState::AwaitingInput,
+                };
+            }
+
+            // items
+            G => {
+                if !get_item(&mut gs.ecs) {
+                    return RunState::AwaitingInput;
+                }
+            }
+            I => return RunState::ShowInventory,
+            D => return RunState::ShowDropItem,
+            R => return RunState::ShowRemoveItem,
+
+            // message log, none of these take a turn
+            PageUp => {
+                gs.ecs.write_resource::<GameLog>().scroll_up();
+                return RunState::AwaitingInput;
+            }
+            PageDown => {
+                gs.ecs.write_resource::<GameLog>().scroll_down();
+                return RunState::AwaitingInput;
+            }
+            M => return RunState::ShowLog { offset: 0 },
+
+            // save and quit
+            Escape => return RunState::SaveGame,
             _ => return RunState::AwaitingInput,
         },
     }
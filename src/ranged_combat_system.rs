@@ -0,0 +1,253 @@
+use super::attack::{self, AttackRoll};
+use super::{
+    Ammunition, CombatStats, DefenseBonus, Equipped, GameLog, GameStats, InPack, LogEntry, Map, Name, Perk, Perks,
+    Position, RangedWeapon, SufferDamage, WantsToShoot,
+};
+use rltk::{BaseMap, Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+/// the tiles a shot from `from` to `to` passes over, ending on the first
+/// creature, wall or closed door in the way, or on `to` when nothing is
+pub fn line_of_fire(map: &Map, from: Point, to: Point, is_creature: impl Fn(Entity) -> bool) -> Vec<Point> {
+    if from == to {
+        return vec![to];
+    }
+
+    let mut path = Vec::new();
+    for step in rltk::line2d(rltk::LineAlg::Bresenham, from, to).into_iter().skip(1) {
+        path.push(step);
+        let idx = map.xy_idx(step.x, step.y);
+        if map.is_opaque(idx) || map.tile_content[idx].iter().any(|ent| is_creature(*ent)) {
+            break;
+        }
+    }
+    path
+}
+
+/// where a blast thrown from `from` at `to` goes off: on the first creature in
+/// the way, or on the last open tile before a wall
+pub fn blast_centre(map: &Map, from: Point, to: Point, is_creature: impl Fn(Entity) -> bool) -> Point {
+    line_of_fire(map, from, to, is_creature)
+        .into_iter()
+        .rev()
+        .find(|pt| !map.is_opaque(map.xy_idx(pt.x, pt.y)))
+        .unwrap_or(from)
+}
+
+/// every tile within `radius` that the centre can see, so walls shield what is behind them
+pub fn blast_area(map: &Map, centre: Point, radius: i32) -> Vec<Point> {
+    rltk::field_of_view(centre, radius, map)
+        .into_iter()
+        .filter(|pt| pt.x >= 0 && pt.x < map.width && pt.y >= 0 && pt.y < map.height)
+        .collect()
+}
+
+/// fires wielded ranged weapons, each shot costs a round of ammunition and
+/// to-hit drops with distance
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, SufferDamage>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameStats>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, Ammunition>,
+        ReadStorage<'a, InPack>,
+        ReadStorage<'a, Perks>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut wants_shoot,
+            map,
+            names,
+            all_combat_stats,
+            positions,
+            mut damages,
+            player_entity,
+            mut stats,
+            mut log,
+            mut rng,
+            equipped,
+            defense_bonuses,
+            weapons,
+            mut ammunition,
+            backpack,
+            perks,
+        ) = data;
+
+        for (ent, shot, name, combat_stats, pos) in
+            (&entities, &wants_shoot, &names, &all_combat_stats, &positions).join()
+        {
+            if combat_stats.hp <= 0 {
+                continue;
+            }
+            // the targeting screen won't offer the shooter's own tile, don't waste a round on it
+            let from = Point::new(pos.x, pos.y);
+            if shot.target == from {
+                continue;
+            }
+            let Some(weapon) = (&equipped, &weapons)
+                .join()
+                .find(|(worn, _weapon)| worn.owner == ent)
+                .map(|(_worn, weapon)| weapon)
+            else {
+                continue;
+            };
+
+            // a round from the first stack in the pack that has any left
+            let Some((ammo_ent, ammo)) = (&entities, &backpack, &mut ammunition)
+                .join()
+                .find(|(_ent, pack, ammo)| pack.owner == ent && ammo.rounds > 0)
+                .map(|(ammo_ent, _pack, ammo)| (ammo_ent, ammo))
+            else {
+                log.push(LogEntry::new(&name.name, "is out of ammunition").color(RGB::named(rltk::GREY)));
+                continue;
+            };
+            ammo.rounds -= 1;
+            if ammo.rounds == 0 {
+                let ammo_name = names.get(ammo_ent).map(|n| n.name.clone()).unwrap_or_default();
+                log.push(LogEntry::new(&name.name, "uses up the last of the").target(ammo_name));
+                entities.delete(ammo_ent).expect("unable to delete spent ammunition");
+            }
+
+            let path = line_of_fire(&map, from, shot.target, |ent| all_combat_stats.get(ent).is_some());
+            let impact = *path.last().unwrap_or(&shot.target);
+            let hit = map.tile_content[map.xy_idx(impact.x, impact.y)]
+                .iter()
+                .find(|target| **target != ent && all_combat_stats.get(**target).is_some())
+                .copied();
+
+            let Some(target) = hit else {
+                log.push(LogEntry::new(&name.name, "shoots and hits nothing").color(RGB::named(rltk::GREY)));
+                continue;
+            };
+            let target_stats = all_combat_stats.get(target).unwrap();
+            let target_name = names.get(target).map(|n| n.name.clone()).unwrap_or_default();
+
+            let defense_bonus: i32 = (&equipped, &defense_bonuses)
+                .join()
+                .filter(|(worn, _bonus)| worn.owner == target)
+                .map(|(_worn, bonus)| bonus.defense)
+                .sum();
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(from, impact);
+            let penalty = if perks.get(ent).is_some_and(|taken| taken.has(Perk::Marksman)) {
+                0
+            } else {
+                attack::range_penalty(distance)
+            };
+            let (roll, amount) = attack::swing(
+                &mut rng,
+                combat_stats.power - penalty,
+                attack::armour_class(target_stats.defense + defense_bonus),
+                weapon.damage,
+            );
+
+            let verb = match roll {
+                AttackRoll::Fumble => "fumbles a shot at",
+                AttackRoll::Miss => "shoots wide of",
+                AttackRoll::Hit => "shoots",
+                AttackRoll::Critical => "lands a critical shot on",
+            };
+            if amount == 0 {
+                log.push(LogEntry::new(&name.name, verb).target(&target_name).color(RGB::named(rltk::GREY)));
+            } else {
+                let color = attack::hit_color(target == *player_entity);
+                log.push(LogEntry::new(&name.name, verb).target(&target_name).amount(amount).color(color));
+                SufferDamage::new_damage(&mut damages, target, amount, Some(ent));
+                if target == *player_entity {
+                    stats.last_hit_by = Some(name.name.clone());
+                }
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileType;
+
+    /// a 10x5 room of floor inside a wall ring
+    fn open_room() -> Map {
+        let mut map = Map::new(1, 12, 7);
+        for y in 1..6 {
+            for x in 1..11 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn a_clear_shot_reaches_the_target() {
+        let map = open_room();
+        let path = line_of_fire(&map, Point::new(1, 3), Point::new(8, 3), |_| false);
+        assert_eq!(path.len(), 7);
+        assert_eq!(path.last(), Some(&Point::new(8, 3)));
+    }
+
+    #[test]
+    fn walls_stop_the_shot() {
+        let mut map = open_room();
+        let wall = map.xy_idx(4, 3);
+        map.tiles[wall] = TileType::Wall;
+        let path = line_of_fire(&map, Point::new(1, 3), Point::new(8, 3), |_| false);
+        assert_eq!(path.last(), Some(&Point::new(4, 3)));
+    }
+
+    #[test]
+    fn the_first_creature_in_the_way_takes_the_shot() {
+        let mut ecs = World::new();
+        let creature = ecs.create_entity().build();
+        let mut map = open_room();
+        let idx = map.xy_idx(5, 3);
+        map.tile_content[idx].push(creature);
+
+        let path = line_of_fire(&map, Point::new(1, 3), Point::new(8, 3), |ent| ent == creature);
+        assert_eq!(path.last(), Some(&Point::new(5, 3)));
+    }
+
+    #[test]
+    fn blasts_against_a_wall_go_off_in_front_of_it() {
+        let mut map = open_room();
+        let wall = map.xy_idx(6, 3);
+        map.tiles[wall] = TileType::Wall;
+        assert_eq!(blast_centre(&map, Point::new(1, 3), Point::new(9, 3), |_| false), Point::new(5, 3));
+    }
+
+    #[test]
+    fn walls_shield_from_blasts() {
+        // a wall right of the centre with open floor behind it
+        let mut map = open_room();
+        for y in 1..6 {
+            let idx = map.xy_idx(6, y);
+            map.tiles[idx] = TileType::Wall;
+        }
+        let area = blast_area(&map, Point::new(4, 3), 4);
+        assert!(area.contains(&Point::new(2, 3)));
+        assert!(area.contains(&Point::new(6, 3)));
+        assert!(!area.contains(&Point::new(7, 3)));
+    }
+
+    #[test]
+    fn shooting_your_own_tile_goes_nowhere() {
+        let map = open_room();
+        assert_eq!(line_of_fire(&map, Point::new(3, 3), Point::new(3, 3), |_| false), vec![Point::new(3, 3)]);
+    }
+}
@@ -0,0 +1,29 @@
+mod raw_structs;
+pub use raw_structs::*;
+
+mod rawmaster;
+pub use rawmaster::*;
+
+use std::sync::OnceLock;
+
+const RAW_PATH: &str = "./raws/spawns.json";
+
+// the copy baked in at build time, used when there is no raws folder next to the game
+const EMBEDDED_RAWS: &str = include_str!("../../raws/spawns.json");
+
+static RAWS: OnceLock<RawMaster> = OnceLock::new();
+
+/// read the templates once at startup, the file on disk wins over the embedded copy
+/// so designers can edit it without rebuilding
+pub fn load_raws() -> Result<(), String> {
+    let data = std::fs::read_to_string(RAW_PATH).unwrap_or_else(|_| EMBEDDED_RAWS.to_string());
+    let raws: Raws = serde_json::from_str(&data).map_err(|e| format!("unable to parse {}: {}", RAW_PATH, e))?;
+    let master = RawMaster::new(raws)?;
+    // loading twice keeps the first set, the templates can't change mid game
+    let _ = RAWS.set(master);
+    Ok(())
+}
+
+pub fn raws() -> &'static RawMaster {
+    RAWS.get().expect("raws are not loaded, call load_raws first")
+}
@@ -0,0 +1,101 @@
+use super::super::{EquipmentSlot, StatusEffect};
+use serde::Deserialize;
+
+/// the whole spawns file, see raws/spawns.json
+#[derive(Deserialize, Debug)]
+pub struct Raws {
+    pub mobs: Vec<RawMob>,
+    pub items: Vec<RawItem>,
+    #[serde(default)]
+    pub props: Vec<RawProp>,
+    pub spawn_table: Vec<RawSpawnEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawRenderable {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawMob {
+    pub name: String,
+    pub renderable: RawRenderable,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    pub vision_range: i32,
+    pub stats: RawMobStats,
+    // for whoever kills it, grows with depth like the stats
+    pub xp: i32,
+    pub scaling: RawMobScaling,
+}
+
+/// how much tougher the mob gets for every level below the first
+#[derive(Deserialize, Debug)]
+pub struct RawMobScaling {
+    pub hp_per_level: i32,
+    // +1 power and defense once every this many levels
+    pub levels_per_power: i32,
+    pub levels_per_defense: i32,
+    pub xp_per_level: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawMobStats {
+    pub max_hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    // dice like "1d6+2", rolled for every hit
+    pub damage: String,
+}
+
+/// every optional field turns into the matching component when present
+#[derive(Deserialize, Debug)]
+pub struct RawItem {
+    pub name: String,
+    pub renderable: RawRenderable,
+    #[serde(default)]
+    pub consumable: bool,
+    pub healing: Option<i32>,
+    pub damage: Option<i32>,
+    pub range: Option<i32>,
+    // blast radius around the target, needs a range
+    pub area_of_effect: Option<i32>,
+    pub status: Option<StatusEffect>,
+    // rounds in a stack of ammunition
+    pub ammo: Option<i32>,
+    pub equip: Option<RawEquip>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawEquip {
+    pub slot: EquipmentSlot,
+    // weapon damage dice, replaces the wielder's natural attack
+    pub damage: Option<String>,
+    // how far a weapon in the Ranged slot shoots
+    pub range: Option<i32>,
+    pub power_bonus: Option<i32>,
+    pub defense_bonus: Option<i32>,
+}
+
+/// scenery placed by map builders rather than the spawn table, doors for now
+#[derive(Deserialize, Debug)]
+pub struct RawProp {
+    pub name: String,
+    pub renderable: RawRenderable,
+    #[serde(default)]
+    pub door: bool,
+}
+
+/// how likely a mob or item is to show up, only between min_depth and max_depth,
+/// the weight moves by weight_per_depth for every level below the first
+#[derive(Deserialize, Debug)]
+pub struct RawSpawnEntry {
+    pub name: String,
+    pub weight: i32,
+    #[serde(default)]
+    pub weight_per_depth: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
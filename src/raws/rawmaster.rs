@@ -0,0 +1,226 @@
+use super::super::spawner::{RENDER_ORDER_CREATURE, RENDER_ORDER_ITEM, RENDER_ORDER_PROP};
+use super::super::{
+    Ammunition, AreaOfEffect, BlocksTile, BlocksVisibility, CombatStats, Consumable, DefenseBonus, Door, EquipmentSlot, Equippable,
+    GrantsExperience, InflictsDamage, InflictsStatus, Item, MeleePowerBonus, MeleeWeapon, Monster, Name, NaturalAttack, Position,
+    ProvidesHealing, RandomTable, Ranged, RangedWeapon, Renderable, SerializeMe, ViewShed,
+};
+use super::{RawRenderable, Raws};
+use rltk::RGB;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use std::collections::HashMap;
+
+pub struct RawMaster {
+    raws: Raws,
+    mob_index: HashMap<String, usize>,
+    item_index: HashMap<String, usize>,
+    prop_index: HashMap<String, usize>,
+}
+
+impl RawMaster {
+    /// index the templates by name and catch typos before the first spawn does
+    pub fn new(raws: Raws) -> Result<RawMaster, String> {
+        let mut mob_index = HashMap::new();
+        for (i, mob) in raws.mobs.iter().enumerate() {
+            check_renderable(&mob.name, &mob.renderable)?;
+            check_dice(&mob.name, &mob.stats.damage)?;
+            if mob.scaling.levels_per_power < 1 || mob.scaling.levels_per_defense < 1 {
+                return Err(format!("{} must scale power and defense at least every level", mob.name));
+            }
+            mob_index.insert(mob.name.clone(), i);
+        }
+
+        let mut item_index = HashMap::new();
+        for (i, item) in raws.items.iter().enumerate() {
+            check_renderable(&item.name, &item.renderable)?;
+            if item.area_of_effect.is_some() && item.range.is_none() {
+                return Err(format!("{} has an area of effect but no range", item.name));
+            }
+            if let Some(equip) = &item.equip {
+                if let Some(damage) = &equip.damage {
+                    check_dice(&item.name, damage)?;
+                }
+                if equip.slot == EquipmentSlot::Ranged && (equip.damage.is_none() || equip.range.is_none()) {
+                    return Err(format!("{} is a ranged weapon without damage or range", item.name));
+                }
+            }
+            if mob_index.contains_key(&item.name) {
+                return Err(format!("{} is both a mob and an item", item.name));
+            }
+            item_index.insert(item.name.clone(), i);
+        }
+
+        let mut prop_index = HashMap::new();
+        for (i, prop) in raws.props.iter().enumerate() {
+            check_renderable(&prop.name, &prop.renderable)?;
+            if mob_index.contains_key(&prop.name) || item_index.contains_key(&prop.name) {
+                return Err(format!("{} is a prop and also a mob or item", prop.name));
+            }
+            prop_index.insert(prop.name.clone(), i);
+        }
+
+        for entry in raws.spawn_table.iter() {
+            if !mob_index.contains_key(&entry.name) && !item_index.contains_key(&entry.name) {
+                return Err(format!("spawn table entry {} has no template", entry.name));
+            }
+        }
+
+        Ok(RawMaster { raws, mob_index, item_index, prop_index })
+    }
+
+    /// every mob and item allowed at this depth, weighted for this depth
+    pub fn spawn_table(&self, depth: i32) -> RandomTable {
+        self.raws
+            .spawn_table
+            .iter()
+            .filter(|e| depth >= e.min_depth && depth <= e.max_depth)
+            .fold(RandomTable::new(), |table, e| {
+                table.add(&e.name, e.weight + (depth - 1) * e.weight_per_depth)
+            })
+    }
+
+    /// mobs, items and props share one namespace, so a name is enough to build any of them
+    pub fn spawn_named_entity(&self, ecs: &mut World, name: &str, x: i32, y: i32, depth: i32, tag: usize) -> Option<Entity> {
+        if self.mob_index.contains_key(name) {
+            self.spawn_named_mob(ecs, name, x, y, depth, tag)
+        } else if self.prop_index.contains_key(name) {
+            self.spawn_named_prop(ecs, name, x, y)
+        } else {
+            self.spawn_named_item(ecs, name, x, y)
+        }
+    }
+
+    /// build a mob from its template, `tag` tells mobs of the same kind apart in the log
+    pub fn spawn_named_mob(&self, ecs: &mut World, name: &str, x: i32, y: i32, depth: i32, tag: usize) -> Option<Entity> {
+        let mob = &self.raws.mobs[*self.mob_index.get(name)?];
+
+        // every level below the first makes the template a bit tougher
+        let level = depth - 1;
+        let scaling = &mob.scaling;
+        let max_hp = mob.stats.max_hp + level * scaling.hp_per_level;
+
+        let mut builder = ecs
+            .create_entity()
+            .with(Position { x, y })
+            .with(renderable(&mob.renderable, RENDER_ORDER_CREATURE))
+            .with(ViewShed {
+                visible_tiles: Vec::new(),
+                range: mob.vision_range,
+                dirty: true,
+            })
+            .with(Monster {})
+            .with(Name {
+                name: format!("{} #{}", mob.name, tag),
+            })
+            .with(CombatStats {
+                max_hp,
+                hp: max_hp,
+                defense: mob.stats.defense + level / scaling.levels_per_defense,
+                power: mob.stats.power + level / scaling.levels_per_power,
+            })
+            .with(NaturalAttack { damage: dice(&mob.stats.damage) })
+            .with(GrantsExperience { xp: mob.xp + level * scaling.xp_per_level });
+        if mob.blocks_tile {
+            builder = builder.with(BlocksTile {});
+        }
+
+        Some(builder.marked::<SimpleMarker<SerializeMe>>().build())
+    }
+
+    pub fn spawn_named_item(&self, ecs: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+        let item = &self.raws.items[*self.item_index.get(name)?];
+
+        let mut builder = ecs
+            .create_entity()
+            .with(Position { x, y })
+            .with(renderable(&item.renderable, RENDER_ORDER_ITEM))
+            .with(Name { name: item.name.clone() })
+            .with(Item {});
+        if item.consumable {
+            builder = builder.with(Consumable {});
+        }
+        if let Some(heal_amount) = item.healing {
+            builder = builder.with(ProvidesHealing { heal_amount });
+        }
+        if let Some(damage) = item.damage {
+            builder = builder.with(InflictsDamage { damage });
+        }
+        if let Some(range) = item.range {
+            builder = builder.with(Ranged { range });
+        }
+        if let Some(radius) = item.area_of_effect {
+            builder = builder.with(AreaOfEffect { radius });
+        }
+        if let Some(effect) = item.status {
+            builder = builder.with(InflictsStatus { effect });
+        }
+        if let Some(rounds) = item.ammo {
+            builder = builder.with(Ammunition { rounds });
+        }
+        if let Some(equip) = &item.equip {
+            builder = builder.with(Equippable { slot: equip.slot });
+            match (equip.slot, &equip.damage) {
+                (EquipmentSlot::Ranged, Some(damage)) => {
+                    builder = builder.with(RangedWeapon { range: equip.range.unwrap(), damage: dice(damage) });
+                }
+                (_, Some(damage)) => builder = builder.with(MeleeWeapon { damage: dice(damage) }),
+                (_, None) => {}
+            }
+            if let Some(power) = equip.power_bonus {
+                builder = builder.with(MeleePowerBonus { power });
+            }
+            if let Some(defense) = equip.defense_bonus {
+                builder = builder.with(DefenseBonus { defense });
+            }
+        }
+
+        Some(builder.marked::<SimpleMarker<SerializeMe>>().build())
+    }
+
+    /// doors start closed, so they block the tile and the view
+    pub fn spawn_named_prop(&self, ecs: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+        let prop = &self.raws.props[*self.prop_index.get(name)?];
+
+        let mut builder = ecs
+            .create_entity()
+            .with(Position { x, y })
+            .with(renderable(&prop.renderable, RENDER_ORDER_PROP))
+            .with(Name { name: prop.name.clone() });
+        if prop.door {
+            builder = builder
+                .with(Door { open: false })
+                .with(BlocksTile {})
+                .with(BlocksVisibility {});
+        }
+
+        Some(builder.marked::<SimpleMarker<SerializeMe>>().build())
+    }
+}
+
+fn check_renderable(name: &str, raw: &RawRenderable) -> Result<(), String> {
+    if raw.glyph.chars().next().is_none() {
+        return Err(format!("{} has an empty glyph", name));
+    }
+    for color in [&raw.fg, &raw.bg] {
+        RGB::from_hex(color).map_err(|_| format!("{} has a bad colour {}", name, color))?;
+    }
+    Ok(())
+}
+
+fn check_dice(name: &str, dice: &str) -> Result<(), String> {
+    rltk::parse_dice_string(dice).map_err(|_| format!("{} has bad damage dice {}", name, dice))?;
+    Ok(())
+}
+
+fn dice(raw: &str) -> rltk::DiceType {
+    rltk::parse_dice_string(raw).unwrap()
+}
+
+fn renderable(raw: &RawRenderable, render_order: i32) -> Renderable {
+    Renderable {
+        glyph: rltk::to_cp437(raw.glyph.chars().next().unwrap()),
+        fg: RGB::from_hex(&raw.fg).unwrap(),
+        bg: RGB::from_hex(&raw.bg).unwrap(),
+        render_order,
+    }
+}
@@ -0,0 +1,333 @@
+use super::*;
+use specs::error::NoError;
+use specs::saveload::{
+    DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+const SAVE_PATH: &str = "./savegame.json";
+
+/// bump this whenever a component or the helper changes shape,
+/// old files are refused instead of half-loaded
+const SAVE_VERSION: u32 = 13;
+
+// one SerializeComponents call per component type, the tuple form only
+// takes a limited number of storages and reads badly anyway; a failed write
+// returns the error from the enclosing function
+macro_rules! serialize_individually {
+    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+            &( $ecs.read_storage::<$type>(), ),
+            &$data.0,
+            &$data.1,
+            &mut $ser,
+        )
+        .map_err(|e| e.to_string())?;
+        )*
+    };
+}
+
+// must list the types in exactly the same order as serialize_individually,
+// a component that fails to parse returns the error from the enclosing function
+macro_rules! deserialize_individually {
+    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        DeserializeComponents::<NoError, _>::deserialize(
+            &mut ( &mut $ecs.write_storage::<$type>(), ),
+            &$data.0,
+            &mut $data.1,
+            &mut $data.2,
+            &mut $de,
+        )
+        .map_err(|e| e.to_string())?;
+        )*
+    };
+}
+
+pub fn does_save_exist() -> bool {
+    Path::new(SAVE_PATH).exists()
+}
+
+/// a failed save leaves the world as it was, the game can go on
+pub fn save_game(ecs: &mut World) -> Result<(), String> {
+    let writer = File::create(SAVE_PATH).map_err(|e| e.to_string())?;
+    save_to_writer(ecs, writer)
+}
+
+fn save_to_writer<W: Write>(ecs: &mut World, writer: W) -> Result<(), String> {
+    // resources are not components, park them on a helper entity for the write
+    let helper = SerializationHelper {
+        map: (*ecs.fetch::<Map>()).clone(),
+        run_state: *ecs.fetch::<RunState>(),
+        seed: ecs.fetch::<Seed>().0,
+        rng: (*ecs.fetch::<rltk::RandomNumberGenerator>()).clone(),
+        stats: (*ecs.fetch::<GameStats>()).clone(),
+    };
+    let helper_ent = ecs
+        .create_entity()
+        .with(helper)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    let result = write_components(ecs, writer);
+
+    ecs.delete_entity(helper_ent).expect("unable to delete helper");
+    result
+}
+
+/// the version, then every saved component type in turn
+fn write_components<W: Write>(ecs: &World, writer: W) -> Result<(), String> {
+    let data = (ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>());
+
+    let mut serializer = serde_json::Serializer::new(writer);
+    serde::Serialize::serialize(&SAVE_VERSION, &mut serializer).map_err(|e| e.to_string())?;
+    serialize_individually!(
+        ecs,
+        serializer,
+        data,
+        Position,
+        Renderable,
+        Player,
+        ViewShed,
+        Monster,
+        Name,
+        BlocksTile,
+        CombatStats,
+        WantsToMelee,
+        Item,
+        InPack,
+        WantsToPickupItem,
+        WantsToDropItem,
+        Consumable,
+        ProvidesHealing,
+        InflictsDamage,
+        Ranged,
+        WantsToUseItem,
+        Equippable,
+        Equipped,
+        MeleePowerBonus,
+        DefenseBonus,
+        WantsToRemoveItem,
+        BlocksVisibility,
+        Door,
+        WantsToOpenDoor,
+        WantsToCloseDoor,
+        MeleeWeapon,
+        NaturalAttack,
+        StatusEffects,
+        InflictsStatus,
+        RangedWeapon,
+        Ammunition,
+        WantsToShoot,
+        AreaOfEffect,
+        Experience,
+        GrantsExperience,
+        Perks,
+        SerializationHelper
+    );
+    Ok(())
+}
+
+/// expects a fresh world from `new_world`, on an error it is left half loaded
+/// and should be thrown away
+pub fn load_game(ecs: &mut World) -> Result<(), String> {
+    let data = fs::read_to_string(SAVE_PATH).map_err(|e| e.to_string())?;
+    load_from_str(ecs, &data)
+}
+
+fn load_from_str(ecs: &mut World, data: &str) -> Result<(), String> {
+    let mut de = serde_json::Deserializer::from_str(data);
+
+    let version: u32 = serde::Deserialize::deserialize(&mut de).map_err(|e| e.to_string())?;
+    if version != SAVE_VERSION {
+        return Err(format!(
+            "save file version {} does not match game version {}",
+            version, SAVE_VERSION
+        ));
+    }
+
+    {
+        let mut d = (
+            &mut ecs.entities(),
+            &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+            &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+        );
+        deserialize_individually!(
+            ecs,
+            de,
+            d,
+            Position,
+            Renderable,
+            Player,
+            ViewShed,
+            Monster,
+            Name,
+            BlocksTile,
+            CombatStats,
+            WantsToMelee,
+            Item,
+            InPack,
+            WantsToPickupItem,
+            WantsToDropItem,
+            Consumable,
+            ProvidesHealing,
+            InflictsDamage,
+            Ranged,
+            WantsToUseItem,
+            Equippable,
+            Equipped,
+            MeleePowerBonus,
+            DefenseBonus,
+            WantsToRemoveItem,
+            BlocksVisibility,
+            Door,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            MeleeWeapon,
+            NaturalAttack,
+            StatusEffects,
+            InflictsStatus,
+            RangedWeapon,
+            Ammunition,
+            WantsToShoot,
+            AreaOfEffect,
+            Experience,
+            GrantsExperience,
+            Perks,
+            SerializationHelper
+        );
+    }
+
+    let (helper_ent, helper) = {
+        let entities = ecs.entities();
+        let helpers = ecs.read_storage::<SerializationHelper>();
+        match (&entities, &helpers).join().next() {
+            Some((ent, helper)) => (ent, helper.clone()),
+            None => return Err("save file has no map".to_string()),
+        }
+    };
+    ecs.delete_entity(helper_ent).expect("unable to delete helper");
+
+    let mut map = helper.map;
+    if map.tiles.len() != (map.width * map.height) as usize {
+        return Err("save file map does not match its size".to_string());
+    }
+    map.tile_content = vec![Vec::new(); (map.width * map.height) as usize];
+    ecs.insert(map);
+    ecs.insert(helper.run_state);
+    ecs.insert(Seed(helper.seed));
+    ecs.insert(helper.rng);
+    ecs.insert(helper.stats);
+
+    let (player_ent, player_pos) = {
+        let entities = ecs.entities();
+        let players = ecs.read_storage::<Player>();
+        let positions = ecs.read_storage::<Position>();
+        match (&entities, &players, &positions).join().next() {
+            Some((ent, _player, pos)) => (ent, rltk::Point::new(pos.x, pos.y)),
+            None => return Err("save file has no player".to_string()),
+        }
+    };
+    ecs.insert(player_ent);
+    ecs.insert(player_pos);
+
+    Ok(())
+}
+
+pub fn delete_save() -> Result<(), String> {
+    if does_save_exist() {
+        fs::remove_file(SAVE_PATH).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_corrupt_save_is_an_error_not_a_crash() {
+        let mut ecs = crate::new_world();
+        assert!(load_from_str(&mut ecs, &format!("{} [{{\"not\": \"a component\"", SAVE_VERSION)).is_err());
+    }
+
+    /// the entity with this name in `ecs`
+    fn named(ecs: &World, name: &str) -> Entity {
+        let names = ecs.read_storage::<Name>();
+        (&ecs.entities(), &names)
+            .join()
+            .find(|(_ent, n)| n.name == name)
+            .map(|(ent, _n)| ent)
+            .unwrap_or_else(|| panic!("no {} after loading", name))
+    }
+
+    #[test]
+    fn entity_references_survive_a_round_trip() {
+        let mut ecs = crate::new_world();
+        let mut map = Map::new(3, 20, 10);
+        let floor = map.xy_idx(4, 5);
+        map.tiles[floor] = TileType::Floor;
+        ecs.insert(map);
+        ecs.insert(RunState::PreRun);
+        ecs.insert(Seed(7));
+        ecs.insert(rltk::RandomNumberGenerator::seeded(7));
+        ecs.insert(GameStats { turns: 12, last_hit_by: None });
+
+        let player = ecs
+            .create_entity()
+            .with(Player {})
+            .with(Name { name: "Neil".to_string() })
+            .with(Position { x: 4, y: 5 })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+        ecs.create_entity()
+            .with(Name { name: "Goblin #1".to_string() })
+            .with(WantsToMelee { target: player })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+        ecs.create_entity()
+            .with(Name { name: "Dagger".to_string() })
+            .with(InPack { owner: player })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+        ecs.create_entity()
+            .with(Name { name: "Shield".to_string() })
+            .with(Equipped { owner: player, slot: EquipmentSlot::Shield })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+
+        let mut saved = Vec::new();
+        save_to_writer(&mut ecs, &mut saved).expect("unable to save");
+
+        // entities already in the new world push the loaded ones onto other ids
+        let mut loaded = crate::new_world();
+        for _ in 0..5 {
+            loaded.create_entity().build();
+        }
+        load_from_str(&mut loaded, std::str::from_utf8(&saved).unwrap()).expect("unable to load");
+
+        let player = named(&loaded, "Neil");
+        assert!(loaded.read_storage::<Player>().get(player).is_some());
+        assert_eq!(loaded.read_storage::<WantsToMelee>().get(named(&loaded, "Goblin #1")).unwrap().target, player);
+        assert_eq!(loaded.read_storage::<InPack>().get(named(&loaded, "Dagger")).unwrap().owner, player);
+        assert_eq!(loaded.read_storage::<Equipped>().get(named(&loaded, "Shield")).unwrap().owner, player);
+
+        assert_eq!(*loaded.fetch::<Entity>(), player);
+        assert_eq!(*loaded.fetch::<rltk::Point>(), rltk::Point::new(4, 5));
+        assert!(*loaded.fetch::<RunState>() == RunState::PreRun);
+        assert_eq!(loaded.fetch::<GameStats>().turns, 12);
+        let map = loaded.fetch::<Map>();
+        assert_eq!((map.depth, map.width, map.height), (3, 20, 10));
+        assert!(map.tiles[map.xy_idx(4, 5)] == TileType::Floor);
+        assert_eq!(map.tile_content.len(), map.tiles.len());
+    }
+
+    #[test]
+    fn a_save_from_another_version_is_refused() {
+        let mut ecs = crate::new_world();
+        assert!(load_from_str(&mut ecs, &format!("{}", SAVE_VERSION + 1)).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use super::{
+    raws, CombatStats, Experience, Map, Name, NaturalAttack, Perks, Player, Position, Renderable, SerializeMe, ViewShed,
+};
+use rltk::RGB;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+/// lower render order is drawn later, so it ends up on top
+pub const RENDER_ORDER_PARTICLE: i32 = -1;
+pub const RENDER_ORDER_PLAYER: i32 = 0;
+pub const RENDER_ORDER_CREATURE: i32 = 1;
+pub const RENDER_ORDER_ITEM: i32 = 2;
+pub const RENDER_ORDER_PROP: i32 = 3;
+
+pub fn player(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: RENDER_ORDER_PLAYER,
+        })
+        .with(Player {})
+        .with(Name {name: "Neil".to_string()})
+        .with(ViewShed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(CombatStats { max_hp: 30, hp: 30, defense: 2, power: 5})
+        .with(NaturalAttack { damage: rltk::DiceType::new(1, 4, 0) })
+        .with(Experience { level: 1, xp: 0 })
+        .with(Perks::default())
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+/// create everything the map builder decided on, tags keep same-named mobs apart
+pub fn spawn_list(ecs: &mut World, map: &Map, spawns: &[(usize, String)]) {
+    let raws = raws::raws();
+    for (tag, (idx, name)) in spawns.iter().enumerate() {
+        let (x, y) = map.idx_xy(*idx);
+        raws.spawn_named_entity(ecs, name, x, y, map.depth, tag);
+    }
+}
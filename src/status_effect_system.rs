@@ -0,0 +1,156 @@
+use super::{CombatStats, GameLog, GameStats, LogEntry, Name, RunState, StatusEffects, StatusKind, SufferDamage};
+use rltk::RGB;
+use specs::prelude::*;
+
+/// applies poison and regeneration and counts every effect down, only on the
+/// monster turn so each effect lasts its number of game turns
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, GameStats>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, run_state, player_entity, mut log, mut stats, names, mut statuses, mut combat_stats, mut damages) =
+            data;
+
+        if *run_state != RunState::MonsterTurn {
+            return;
+        }
+
+        let mut recovered = Vec::new();
+        for (ent, effects) in (&entities, &mut statuses).join() {
+            let name = if ent == *player_entity {
+                "You".to_string()
+            } else {
+                names.get(ent).map(|n| n.name.clone()).unwrap_or_default()
+            };
+
+            for effect in effects.active.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poison => {
+                        SufferDamage::new_damage(&mut damages, ent, effect.potency, None);
+                        let verb = if ent == *player_entity { "suffer from poison" } else { "suffers from poison" };
+                        log.push(
+                            LogEntry::new(&name, verb)
+                                .amount(effect.potency)
+                                .color(RGB::named(rltk::GREEN)),
+                        );
+                        if ent == *player_entity {
+                            stats.last_hit_by = Some("poison".to_string());
+                        }
+                    }
+                    StatusKind::Regeneration => {
+                        if let Some(combat) = combat_stats.get_mut(ent) {
+                            combat.hp = (combat.hp + effect.potency).min(combat.max_hp);
+                        }
+                    }
+                    StatusKind::Confusion | StatusKind::Sleep => {}
+                }
+                effect.turns -= 1;
+            }
+
+            // only the player hears about their own effects ending
+            if ent == *player_entity {
+                for effect in effects.active.iter().filter(|effect| effect.turns <= 0) {
+                    log.push(LogEntry::new("You", format!("are no longer {}", effect.kind.describe().to_lowercase())));
+                }
+            }
+            effects.active.retain(|effect| effect.turns > 0);
+            if effects.active.is_empty() {
+                recovered.push(ent);
+            }
+        }
+
+        for ent in recovered {
+            statuses.remove(ent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusEffect;
+
+    fn world_with(effects: Vec<StatusEffect>) -> (World, Entity) {
+        let mut ecs = World::new();
+        ecs.register::<Name>();
+        ecs.register::<StatusEffects>();
+        ecs.register::<CombatStats>();
+        ecs.register::<SufferDamage>();
+        ecs.insert(RunState::MonsterTurn);
+        ecs.insert(GameLog::default());
+        ecs.insert(GameStats::default());
+
+        let player = ecs
+            .create_entity()
+            .with(CombatStats { max_hp: 30, hp: 20, defense: 0, power: 0 })
+            .with(StatusEffects { active: effects })
+            .build();
+        ecs.insert(player);
+        (ecs, player)
+    }
+
+    fn tick(ecs: &mut World) {
+        StatusEffectSystem {}.run_now(ecs);
+        ecs.maintain();
+    }
+
+    #[test]
+    fn poison_hurts_every_turn_until_it_wears_off() {
+        let poison = StatusEffect { kind: StatusKind::Poison, turns: 2, potency: 3 };
+        let (mut ecs, player) = world_with(vec![poison]);
+
+        let amounts = |ecs: &World| {
+            ecs.read_storage::<SufferDamage>().get(player).unwrap().hits.iter().map(|hit| hit.amount).collect::<Vec<_>>()
+        };
+        tick(&mut ecs);
+        assert_eq!(amounts(&ecs), vec![3]);
+        tick(&mut ecs);
+        assert_eq!(amounts(&ecs), vec![3, 3]);
+        assert!(ecs.read_storage::<StatusEffects>().get(player).is_none());
+    }
+
+    #[test]
+    fn regeneration_stops_at_max_hp() {
+        let regeneration = StatusEffect { kind: StatusKind::Regeneration, turns: 20, potency: 4 };
+        let (mut ecs, player) = world_with(vec![regeneration]);
+
+        for _ in 0..5 {
+            tick(&mut ecs);
+        }
+        assert_eq!(ecs.read_storage::<CombatStats>().get(player).unwrap().hp, 30);
+    }
+
+    #[test]
+    fn nothing_ticks_outside_the_monster_turn() {
+        let sleep = StatusEffect { kind: StatusKind::Sleep, turns: 1, potency: 0 };
+        let (mut ecs, player) = world_with(vec![sleep]);
+
+        *ecs.write_resource::<RunState>() = RunState::PlayerTurn;
+        tick(&mut ecs);
+        assert!(ecs.read_storage::<StatusEffects>().get(player).unwrap().has(StatusKind::Sleep));
+    }
+
+    #[test]
+    fn repeated_effects_refresh_instead_of_stacking() {
+        let (ecs, player) = world_with(Vec::new());
+        let mut statuses = ecs.write_storage::<StatusEffects>();
+        StatusEffects::add(&mut statuses, player, StatusEffect { kind: StatusKind::Confusion, turns: 2, potency: 0 });
+        StatusEffects::add(&mut statuses, player, StatusEffect { kind: StatusKind::Confusion, turns: 5, potency: 0 });
+
+        let active = &statuses.get(player).unwrap().active;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].turns, 5);
+    }
+}